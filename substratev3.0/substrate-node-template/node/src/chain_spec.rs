@@ -1,6 +1,6 @@
 use node_template_runtime::{
-	AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-	SystemConfig, WASM_BINARY,
+	AccountId, AuraConfig, Balance, BalancesConfig, GenesisConfig, GrandpaConfig,
+	KittiesModuleConfig, PoeSpfModuleConfig, Signature, SudoConfig, SystemConfig, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -122,6 +122,21 @@ pub fn local_testnet_config() -> Result<ChainSpec, String> {
 	))
 }
 
+/// Pre-minted kitties as `(owner, dna, market price)`: a breeding pair for the first endowed
+/// account and a kitty on the market for the second one.
+fn genesis_kitties(endowed_accounts: &[AccountId]) -> Vec<(AccountId, [u8; 16], Option<Balance>)> {
+	let mut kitties = Vec::new();
+	if let Some(first) = endowed_accounts.get(0) {
+		// A male and a female, see `pallet_kitties::dna` for the layout.
+		kitties.push((first.clone(), [0x12; 16], None));
+		kitties.push((first.clone(), [0xc5; 16], None));
+	}
+	if let Some(second) = endowed_accounts.get(1) {
+		kitties.push((second.clone(), [0x6e; 16], Some(1_000_000)));
+	}
+	kitties
+}
+
 /// Configure initial storage state for FRAME modules.
 fn testnet_genesis(
 	wasm_binary: &[u8],
@@ -150,5 +165,7 @@ fn testnet_genesis(
 			// Assign network admin rights.
 			key: root_key,
 		},
+		poe_spf_module: PoeSpfModuleConfig::default(),
+		kitties_module: KittiesModuleConfig { kitties: genesis_kitties(&endowed_accounts) },
 	}
 }
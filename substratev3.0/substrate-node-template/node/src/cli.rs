@@ -38,4 +38,11 @@ pub enum Subcommand {
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
 	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
 	Benchmark(frame_benchmarking_cli::BenchmarkCmd),
+
+	/// Build a Merkle tree and inclusion proofs for the files of a directory.
+	#[structopt(name = "merkle-tree")]
+	MerkleTree(crate::poe::MerkleTreeCmd),
+
+	/// Hash a file into a claim and check whether it is already claimed.
+	Poe(crate::poe::PoeCmd),
 }
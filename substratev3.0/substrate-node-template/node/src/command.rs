@@ -125,6 +125,17 @@ pub fn run() -> sc_cli::Result<()> {
 				     `--features runtime-benchmarks`."
 					.into())
 			},
+		Some(Subcommand::MerkleTree(cmd)) => cmd.run(),
+		Some(Subcommand::Poe(cmd)) =>
+			if let Some(state) = &cmd.state {
+				cmd.run_on_state(state)
+			} else {
+				let runner = cli.create_runner(cmd)?;
+				runner.sync_run(|config| {
+					let PartialComponents { client, .. } = service::new_partial(&config)?;
+					cmd.run(client)
+				})
+			},
 		None => {
 			let runner = cli.create_runner(&cli.run)?;
 			runner.run_node_until_exit(|config| async move {
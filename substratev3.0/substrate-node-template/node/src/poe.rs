@@ -0,0 +1,181 @@
+//! Proof of existence command line utilities.
+
+use std::{
+	fs,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use codec::Decode;
+use node_template_runtime::{AccountId, BlockNumber, Runtime};
+use pallet_poe_spf::merkle;
+use sc_cli::{CliConfiguration, DatabaseParams, PruningParams, SharedParams};
+use sc_client_api::{backend::Backend, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::{hashing::blake2_256, hexdisplay::HexDisplay, storage::StorageKey};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Block as BlockT, Get},
+	BuildStorage,
+};
+use structopt::StructOpt;
+
+use crate::chain_spec::ChainSpec;
+
+/// The `poe` command, used to notarize a file and check whether it is already claimed.
+///
+/// The claim of a file is the full 32-byte blake2_256 hash of its content; the printed bytes are
+/// passed to `create_claim`. The claim is looked up in the best block of the local chain
+/// database, or in a state exported with `export-state` when `--state` is given. The command
+/// fails when the hash is longer than the runtime's `ClaimLimit`, since such a claim cannot be
+/// created.
+#[derive(Debug, StructOpt)]
+pub struct PoeCmd {
+	/// File to notarize.
+	#[structopt(parse(from_os_str))]
+	pub file: PathBuf,
+
+	/// Look the claim up in a state exported with `export-state` instead of the chain database.
+	#[structopt(long, parse(from_os_str))]
+	pub state: Option<PathBuf>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+impl PoeCmd {
+	// The claim of a file is the hash of its content. It is never truncated: a shorter prefix of
+	// the hash could be matched by another file with little effort.
+	fn claim(&self) -> sc_cli::Result<Vec<u8>> {
+		let content = fs::read(&self.file)?;
+		let limit = <Runtime as pallet_poe_spf::Config>::ClaimLimit::get();
+		let hash = blake2_256(&content);
+		if hash.len() > limit {
+			return Err(format!(
+				"The claim 0x{} is {} bytes long, but the runtime only accepts claims of up to {} \
+				 bytes (ClaimLimit).",
+				HexDisplay::from(&hash),
+				hash.len(),
+				limit,
+			)
+			.into())
+		}
+		Ok(hash.to_vec())
+	}
+
+	/// Look the claim up in the best block of the chain database.
+	pub fn run<B, BA, C>(&self, client: Arc<C>) -> sc_cli::Result<()>
+	where
+		B: BlockT,
+		BA: Backend<B>,
+		C: StorageProvider<B, BA> + HeaderBackend<B>,
+	{
+		let claim = self.claim()?;
+		let key = StorageKey(pallet_poe_spf::Proofs::<Runtime>::hashed_key_for(&claim));
+		let at = BlockId::Hash(client.info().best_hash);
+
+		let value = client.storage(&at, &key)?.map(|data| data.0);
+		print_claim(&claim, value)
+	}
+
+	/// Look the claim up in a state exported with `export-state`.
+	pub fn run_on_state(&self, state: &Path) -> sc_cli::Result<()> {
+		let claim = self.claim()?;
+		let key = pallet_poe_spf::Proofs::<Runtime>::hashed_key_for(&claim);
+
+		let storage = ChainSpec::from_json_file(state.to_path_buf())?.build_storage()?;
+
+		let value = storage.top.get(&key).cloned();
+		print_claim(&claim, value)
+	}
+}
+
+// Print the claim, and its owner and block when it is claimed.
+fn print_claim(claim: &[u8], value: Option<Vec<u8>>) -> sc_cli::Result<()> {
+	println!("claim: 0x{}", HexDisplay::from(&claim));
+
+	match value {
+		Some(value) => {
+			let (owner, block_number) = <(AccountId, BlockNumber)>::decode(&mut &value[..])
+				.map_err(|e| format!("Invalid proof in storage: {:?}", e))?;
+			println!("owner: {}", owner);
+			println!("since block: {}", block_number);
+		},
+		None => println!("not claimed"),
+	}
+
+	Ok(())
+}
+
+impl CliConfiguration for PoeCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
+
+/// The `merkle-tree` command, used to anchor a directory of files with a single claim.
+///
+/// Every regular file of the directory becomes a leaf (the blake2_256 hash of a 0x00 byte
+/// followed by its content, see `merkle::hash_leaf`), ordered by file name. The printed root is
+/// passed to `create_merkle_claim`, and the printed proofs are later checked with the
+/// `poe_verifyInclusion` RPC.
+#[derive(Debug, StructOpt)]
+pub struct MerkleTreeCmd {
+	/// Directory whose files become the leaves of the tree.
+	#[structopt(parse(from_os_str))]
+	pub dir: PathBuf,
+}
+
+impl MerkleTreeCmd {
+	/// Run the command.
+	pub fn run(&self) -> sc_cli::Result<()> {
+		let mut files = Vec::new();
+		for entry in fs::read_dir(&self.dir)? {
+			let path = entry?.path();
+			if path.is_file() {
+				files.push(path);
+			}
+		}
+		files.sort();
+
+		let leaves = files
+			.iter()
+			.map(|path| fs::read(path).map(|content| merkle::hash_leaf(&content)))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		let root = merkle::merkle_root(&leaves).ok_or("The directory contains no files.")?;
+
+		println!("root: 0x{}", HexDisplay::from(&root));
+		println!("leaves: {}", leaves.len());
+		for (index, (path, leaf)) in files.iter().zip(leaves.iter()).enumerate() {
+			let proof = merkle::merkle_proof(&leaves, index)
+				.expect("index is within the leaves; qed")
+				.iter()
+				.map(|node| format!("0x{}", HexDisplay::from(node)))
+				.collect::<Vec<_>>();
+
+			println!("{}", path.display());
+			println!("  leaf: 0x{}", HexDisplay::from(leaf));
+			println!("  proof: [{}]", proof.join(", "));
+		}
+
+		Ok(())
+	}
+}
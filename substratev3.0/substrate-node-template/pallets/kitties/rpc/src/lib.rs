@@ -0,0 +1,70 @@
+//! RPC interface for the kitties pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+pub use pallet_kitties_runtime_api::{KittiesApi as KittiesRuntimeApi, KittyAttributes};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+/// Kitties RPC methods.
+#[rpc]
+pub trait KittiesApi<BlockHash, KittyIndex> {
+	/// The gender, color, pattern and rarity decoded from the DNA of `kitty_id`.
+	///
+	/// Returns `null` if there is no such kitty.
+	#[rpc(name = "kitties_attributes")]
+	fn kitty_attributes(
+		&self,
+		kitty_id: KittyIndex,
+		at: Option<BlockHash>,
+	) -> Result<Option<KittyAttributes>>;
+}
+
+/// A struct that implements the [`KittiesApi`].
+pub struct Kitties<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Kitties<C, B> {
+	/// Create new `Kitties` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code for failures while calling into the runtime.
+const RUNTIME_ERROR: i64 = 1;
+
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Runtime error".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C, Block, KittyIndex> KittiesApi<<Block as BlockT>::Hash, KittyIndex> for Kitties<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static,
+	C: ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block>,
+	C::Api: KittiesRuntimeApi<Block, KittyIndex>,
+	KittyIndex: Codec,
+{
+	fn kitty_attributes(
+		&self,
+		kitty_id: KittyIndex,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Option<KittyAttributes>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.kitty_attributes(&at, kitty_id).map_err(runtime_error_into_rpc_err)
+	}
+}
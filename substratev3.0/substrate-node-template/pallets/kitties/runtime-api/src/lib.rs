@@ -0,0 +1,15 @@
+//! Runtime API definition for the kitties pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+pub use pallet_kitties::dna::KittyAttributes;
+
+sp_api::decl_runtime_apis! {
+	pub trait KittiesApi<KittyIndex> where
+		KittyIndex: Codec,
+	{
+		/// The attributes decoded from the DNA of `kitty_id`, or `None` if there is no such kitty.
+		fn kitty_attributes(kitty_id: KittyIndex) -> Option<KittyAttributes>;
+	}
+}
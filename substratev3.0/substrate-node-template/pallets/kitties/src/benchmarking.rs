@@ -0,0 +1,231 @@
+//! Benchmarking setup for pallet-kitties
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as KittiesPallet;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, Get, OnFinalize};
+use frame_system::RawOrigin;
+use sp_runtime::traits::{Bounded, One};
+use sp_std::vec::Vec;
+
+const SEED: u32 = 0;
+
+// 公的和母的dna，生kitty时父母要一公一母
+const MALE: [u8; 16] = [0u8; 16];
+const FEMALE: [u8; 16] = [255u8; 16];
+
+// 让账户付得起锁定、买kitty和出价的钱
+fn rich<T: Config>(who: T::AccountId) -> T::AccountId {
+    T::Currency::make_free_balance_be(&who, BalanceOf::<T>::max_value() / 2u32.into());
+    who
+}
+
+fn signed<T: Config>(who: &T::AccountId) -> T::Origin {
+    RawOrigin::Signed(who.clone()).into()
+}
+
+// 创建一个kitty，把dna换成指定的
+fn create_kitty<T: Config>(owner: &T::AccountId, dna: [u8; 16]) -> T::KittyIndex {
+    KittiesPallet::<T>::create(signed::<T>(owner)).expect("kitty can be created");
+    let kitty_id = KittiesCount::<T>::get().expect("kitty was just created");
+    Kitties::<T>::mutate(kitty_id, |kitty| {
+        kitty.as_mut().expect("kitty was just created").dna = dna
+    });
+    kitty_id
+}
+
+// 由另一个账户创建再转给owner，卖的时候要分版税
+fn create_resold_kitty<T: Config>(owner: &T::AccountId, index: u32) -> T::KittyIndex {
+    let creator = rich::<T>(account("creator", index, SEED));
+    let kitty_id = create_kitty::<T>(&creator, MALE);
+    KittiesPallet::<T>::transfer(signed::<T>(&creator), owner.clone(), kitty_id)
+        .expect("kitty can be transferred");
+    kitty_id
+}
+
+// 挂单卖，同时挂出来配种，转移和删除时都要撤掉
+fn list_everywhere<T: Config>(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+    let expires_at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+    KittiesPallet::<T>::market(signed::<T>(owner), kitty_id, 100u32.into(), None)
+        .expect("kitty can be listed");
+    KittiesPallet::<T>::list_sire(signed::<T>(owner), kitty_id, 100u32.into(), expires_at)
+        .expect("sire can be listed");
+}
+
+benchmarks! {
+    create {
+        let caller = rich::<T>(whitelisted_caller());
+    }: _(RawOrigin::Signed(caller.clone()))
+    verify {
+        // 创世区块里可能已经有kitty，新kitty的id是当前的kitty数
+        let kitty_id = KittiesCount::<T>::get().expect("kitty was just created");
+        assert_eq!(KittiesPallet::<T>::owner(kitty_id), Some(caller));
+    }
+
+    transfer {
+        let caller = rich::<T>(whitelisted_caller());
+        let receiver = rich::<T>(account("receiver", 0, SEED));
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        list_everywhere::<T>(&caller, kitty_id);
+    }: _(RawOrigin::Signed(caller), receiver.clone(), kitty_id)
+    verify {
+        assert_eq!(KittiesPallet::<T>::owner(kitty_id), Some(receiver));
+    }
+
+    breed {
+        let caller = rich::<T>(whitelisted_caller());
+        let mother = create_kitty::<T>(&caller, FEMALE);
+        let father = create_kitty::<T>(&caller, MALE);
+    }: _(RawOrigin::Signed(caller.clone()), mother, father)
+    verify {
+        let child = KittiesCount::<T>::get().expect("child was just bred");
+        assert_eq!(KittiesPallet::<T>::owner(child), Some(caller));
+    }
+
+    approve_siring {
+        let caller = rich::<T>(whitelisted_caller());
+        let approved: T::AccountId = account("approved", 0, SEED);
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+    }: _(RawOrigin::Signed(caller), kitty_id, approved.clone())
+    verify {
+        assert_eq!(SireApprovals::<T>::get(kitty_id), Some(approved));
+    }
+
+    market {
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        let price: BalanceOf<T> = 100u32.into();
+    }: _(RawOrigin::Signed(caller), kitty_id, price, None)
+    verify {
+        assert_eq!(KittiesPallet::<T>::kitties_market(kitty_id), Some(price));
+    }
+
+    unlist {
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        KittiesPallet::<T>::market(signed::<T>(&caller), kitty_id, 100u32.into(), None)?;
+    }: _(RawOrigin::Signed(caller), kitty_id)
+    verify {
+        assert_eq!(KittiesPallet::<T>::kitties_market(kitty_id), None);
+    }
+
+    release {
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        list_everywhere::<T>(&caller, kitty_id);
+    }: _(RawOrigin::Signed(caller), kitty_id)
+    verify {
+        assert_eq!(KittiesPallet::<T>::owner(kitty_id), None);
+    }
+
+    // 创建者不是买卖双方，要分版税
+    buy {
+        let seller = rich::<T>(account("seller", 0, SEED));
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_resold_kitty::<T>(&seller, 0);
+        let price: BalanceOf<T> = 1_000u32.into();
+        KittiesPallet::<T>::market(signed::<T>(&seller), kitty_id, price, None)?;
+    }: _(RawOrigin::Signed(caller.clone()), kitty_id, price, None)
+    verify {
+        assert_eq!(KittiesPallet::<T>::owner(kitty_id), Some(caller));
+    }
+
+    create_auction {
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        let end = frame_system::Pallet::<T>::block_number() + 10u32.into();
+    }: _(RawOrigin::Signed(caller), kitty_id, 100u32.into(), 10u32.into(), end)
+    verify {
+        assert!(Auctions::<T>::contains_key(kitty_id));
+    }
+
+    // 退回上一个出价，并且快结束了，要把拍卖挪到新的结束区块
+    bid {
+        let seller = rich::<T>(account("seller", 0, SEED));
+        let bidder = rich::<T>(account("bidder", 0, SEED));
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&seller, MALE);
+        let end = frame_system::Pallet::<T>::block_number() + One::one();
+        KittiesPallet::<T>::create_auction(
+            signed::<T>(&seller),
+            kitty_id,
+            100u32.into(),
+            10u32.into(),
+            end,
+        )?;
+        KittiesPallet::<T>::bid(signed::<T>(&bidder), kitty_id, 100u32.into())?;
+        let amount: BalanceOf<T> = 110u32.into();
+    }: _(RawOrigin::Signed(caller.clone()), kitty_id, amount)
+    verify {
+        assert_eq!(
+            Auctions::<T>::get(kitty_id).and_then(|a| a.highest_bid),
+            Some((caller, amount))
+        );
+    }
+
+    list_sire {
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        let expires_at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+        let price: BalanceOf<T> = 100u32.into();
+    }: _(RawOrigin::Signed(caller), kitty_id, price, expires_at)
+    verify {
+        assert_eq!(SireListings::<T>::get(kitty_id), Some((price, expires_at)));
+    }
+
+    unlist_sire {
+        let caller = rich::<T>(whitelisted_caller());
+        let kitty_id = create_kitty::<T>(&caller, MALE);
+        let expires_at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+        KittiesPallet::<T>::list_sire(signed::<T>(&caller), kitty_id, 100u32.into(), expires_at)?;
+    }: _(RawOrigin::Signed(caller), kitty_id)
+    verify {
+        assert_eq!(SireListings::<T>::get(kitty_id), None);
+    }
+
+    breed_with_sire {
+        let sire_owner = rich::<T>(account("sire_owner", 0, SEED));
+        let caller = rich::<T>(whitelisted_caller());
+        let sire_id = create_kitty::<T>(&sire_owner, MALE);
+        let kitty_id = create_kitty::<T>(&caller, FEMALE);
+        let expires_at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+        let fee: BalanceOf<T> = 100u32.into();
+        KittiesPallet::<T>::list_sire(signed::<T>(&sire_owner), sire_id, fee, expires_at)?;
+    }: _(RawOrigin::Signed(caller.clone()), kitty_id, sire_id, fee)
+    verify {
+        let child = KittiesCount::<T>::get().expect("child was just bred");
+        assert_eq!(KittiesPallet::<T>::owner(child), Some(caller));
+    }
+
+    // 结算在同一个区块结束的a个拍卖，每个都有人出价，都要分版税
+    on_finalize {
+        let a in 1 .. T::MaxAuctionsPerBlock::get();
+        let end = frame_system::Pallet::<T>::block_number() + 10u32.into();
+        let mut bidders = Vec::new();
+        for i in 0 .. a {
+            let seller = rich::<T>(account("seller", i, SEED));
+            let bidder = rich::<T>(account("bidder", i, SEED));
+            let kitty_id = create_resold_kitty::<T>(&seller, i);
+            KittiesPallet::<T>::create_auction(
+                signed::<T>(&seller),
+                kitty_id,
+                100u32.into(),
+                10u32.into(),
+                end,
+            )?;
+            KittiesPallet::<T>::bid(signed::<T>(&bidder), kitty_id, 100u32.into())?;
+            bidders.push((kitty_id, bidder));
+        }
+    }: {
+        KittiesPallet::<T>::on_finalize(end);
+    }
+    verify {
+        for (kitty_id, bidder) in bidders {
+            assert_eq!(KittiesPallet::<T>::owner(kitty_id), Some(bidder));
+        }
+    }
+}
+
+impl_benchmark_test_suite!(KittiesPallet, crate::mock::new_test_ext(), crate::mock::Test);
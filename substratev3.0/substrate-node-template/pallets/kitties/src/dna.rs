@@ -0,0 +1,97 @@
+//! 从kitty的dna解出性别、颜色、花纹和稀有度。
+//!
+//! 只用dna的前4个字节，同样的dna总是解出同样的属性：
+//!
+//! | 字节 | 位      | 属性                                                      |
+//! |------|---------|-----------------------------------------------------------|
+//! | 0    | 最高位  | 性别，0是公，1是母                                        |
+//! | 1    | 高3位   | 颜色，按 [`Color`] 的顺序                                  |
+//! | 2    | 高2位   | 花纹，按 [`Pattern`] 的顺序                                |
+//! | 3    | 全部    | 稀有度：0-127普通，128-191少见，192-239稀有，240-253史诗，254-255传说 |
+
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Gender {
+    Male,
+    Female,
+}
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Color {
+    Black,
+    White,
+    Grey,
+    Orange,
+    Cream,
+    Brown,
+    Blue,
+    Lilac,
+}
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Pattern {
+    Solid,
+    Tabby,
+    Spotted,
+    Calico,
+}
+
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+/// kitty的属性
+#[derive(Encode, Decode, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct KittyAttributes {
+    pub gender: Gender,
+    pub color: Color,
+    pub pattern: Pattern,
+    pub rarity: Rarity,
+}
+
+/// 从dna解出属性
+pub fn decode(dna: &[u8; 16]) -> KittyAttributes {
+    let gender = if dna[0] >> 7 == 0 { Gender::Male } else { Gender::Female };
+
+    let color = match dna[1] >> 5 {
+        0 => Color::Black,
+        1 => Color::White,
+        2 => Color::Grey,
+        3 => Color::Orange,
+        4 => Color::Cream,
+        5 => Color::Brown,
+        6 => Color::Blue,
+        _ => Color::Lilac,
+    };
+
+    let pattern = match dna[2] >> 6 {
+        0 => Pattern::Solid,
+        1 => Pattern::Tabby,
+        2 => Pattern::Spotted,
+        _ => Pattern::Calico,
+    };
+
+    let rarity = match dna[3] {
+        0..=127 => Rarity::Common,
+        128..=191 => Rarity::Uncommon,
+        192..=239 => Rarity::Rare,
+        240..=253 => Rarity::Epic,
+        _ => Rarity::Legendary,
+    };
+
+    KittyAttributes { gender, color, pattern, rarity }
+}
@@ -2,32 +2,100 @@
 
 pub use pallet::*;
 
+pub mod dna;
+pub mod weights;
+
+pub use weights::WeightInfo;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub mod migrations;
+
 #[frame_support::pallet]
 pub mod pallet {
+    use crate::{
+        dna::{self, KittyAttributes},
+        weights::WeightInfo,
+    };
     use codec::{Decode, Encode};
     use frame_support::{
         dispatch::DispatchResult,
         pallet_prelude::*,
         traits::{
-            Currency, ExistenceRequirement, LockableCurrency, Randomness, ReservableCurrency,
+            fungibles, Currency, ExistenceRequirement, LockableCurrency, Randomness,
+            ReservableCurrency,
         },
+        transactional,
     };
     use frame_system::pallet_prelude::*;
     use sp_io::hashing::blake2_128;
-    use sp_runtime::traits::{AtLeast32BitUnsigned, Bounded, Member, One};
+    use sp_std::vec::Vec;
+    use sp_runtime::{
+        traits::{AtLeast32BitUnsigned, Bounded, Member, One, Saturating, Zero},
+        Permill,
+    };
+
+    // 一个kitty的dna、所有者和市场上的价钱
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    pub struct KittyInfo<AccountId, Balance> {
+        pub dna: [u8; 16],
+        pub owner: AccountId,
+        // 挂单卖的价钱，没挂单是None
+        pub price: Option<Balance>,
+    }
+
+    pub type KittyInfoOf<T> = KittyInfo<<T as frame_system::Config>::AccountId, BalanceOf<T>>;
+
+    // 存储的版本，用来判断需不需要迁移
+    #[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+    pub enum Releases {
+        // dna、所有者和价钱分开存在 Kitties、Owner、KittiesMarket 里
+        V1,
+        // 当前的存储，都存在 Kitties 的 KittyInfo 里
+        V2,
+    }
 
-    #[derive(Encode, Decode, Debug, PartialEq)]
-    pub struct Kitty(pub [u8; 16]);
+    impl Default for Releases {
+        fn default() -> Self {
+            Releases::V1
+        }
+    }
+
+    // 拍卖：出价最高的人在结束区块买走kitty
+    #[derive(Encode, Decode, Clone, Debug, PartialEq)]
+    pub struct Auction<AccountId, Balance, BlockNumber> {
+        // 卖家
+        pub seller: AccountId,
+        // 第一次出价不能低于保留价
+        pub reserve_price: Balance,
+        // 后面的出价至少要比最高价多这么多
+        pub min_increment: Balance,
+        // 在这个区块结束时成交
+        pub end: BlockNumber,
+        // 最高出价的人和价钱，出价的钱是锁定的
+        pub highest_bid: Option<(AccountId, Balance)>,
+    }
+
+    pub type AuctionOf<T> = Auction<
+        <T as frame_system::Config>::AccountId,
+        BalanceOf<T>,
+        <T as frame_system::Config>::BlockNumber,
+    >;
 
     pub type BalanceOf<T> =
         <<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
+    pub type AssetIdOf<T> = <<T as Config>::Assets as fungibles::Inspect<
+        <T as frame_system::Config>::AccountId,
+    >>::AssetId;
+
     #[pallet::config]
     pub trait Config: frame_system::Config {
         type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
@@ -37,6 +105,24 @@ pub mod pallet {
             + ReservableCurrency<Self::AccountId>;
         #[pallet::constant]
         type MinimumVotingLock: Get<BalanceOf<Self>>;
+        // 每个账户最多能有多少个kitty
+        #[pallet::constant]
+        type MaxKittiesOwned: Get<u32>;
+        // kitty生一次以后要等多少个区块才能再生
+        #[pallet::constant]
+        type BreedingCooldown: Get<Self::BlockNumber>;
+        // 拍卖结束前这么多个区块内有人出价，结束区块就往后推到出价区块加上这么多个区块
+        #[pallet::constant]
+        type AuctionExtension: Get<Self::BlockNumber>;
+        // 一个区块最多结束多少个拍卖
+        #[pallet::constant]
+        type MaxAuctionsPerBlock: Get<u32>;
+        // 卖kitty时分给创建者的比例
+        #[pallet::constant]
+        type CreatorRoyalty: Get<Permill>;
+        // 挂单时可以用来计价的资产，买的时候用这个资产付钱
+        type Assets: fungibles::Transfer<Self::AccountId, Balance = BalanceOf<Self>>;
+        type WeightInfo: WeightInfo;
     }
 
     #[pallet::pallet]
@@ -47,20 +133,96 @@ pub mod pallet {
     #[pallet::getter(fn kitties_count)]
     pub type KittiesCount<T: Config> = StorageValue<_, T::KittyIndex>;
 
+    // 存储的版本，没有迁移过的节点是V1
+    #[pallet::storage]
+    #[pallet::getter(fn storage_version)]
+    pub type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
     #[pallet::storage]
     #[pallet::getter(fn kitties)]
-    pub type Kitties<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<Kitty>, ValueQuery>;
+    pub type Kitties<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, KittyInfoOf<T>>;
+
+    // 按所有者索引的kitty，用来列出一个账户的所有kitty
+    #[pallet::storage]
+    #[pallet::getter(fn owned_kitties)]
+    pub type OwnedKitties<T: Config> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        T::AccountId,
+        Blake2_128Concat,
+        T::KittyIndex,
+        (),
+    >;
+
+    // 每个账户有多少个kitty
+    #[pallet::storage]
+    #[pallet::getter(fn owned_kitties_count)]
+    pub type OwnedKittiesCount<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::AccountId, u32, ValueQuery>;
+
+    // kitty是谁创建或者生的，卖kitty时要分版税给他
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_creators)]
+    pub type KittyCreators<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId>;
+
+    // kitty的父母，自己创建的kitty没有父母
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_parents)]
+    pub type KittyParents<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, (T::KittyIndex, T::KittyIndex)>;
+
+    // kitty是第几代，自己创建的是第0代
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_generation)]
+    pub type KittyGeneration<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, u32, ValueQuery>;
+
+    // kitty从哪个区块开始可以再生
+    #[pallet::storage]
+    #[pallet::getter(fn next_breed_at)]
+    pub type NextBreedAt<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, T::BlockNumber, ValueQuery>;
+
+    // kitty的所有者允许谁用这个kitty来生，用一次就作废
+    #[pallet::storage]
+    #[pallet::getter(fn sire_approvals)]
+    pub type SireApprovals<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, T::AccountId>;
+
+    // 每个kitty锁定了多少钱，kitty转给谁就由谁锁定
+    #[pallet::storage]
+    #[pallet::getter(fn kitty_deposits)]
+    pub type KittyDeposits<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, BalanceOf<T>, ValueQuery>;
+
+    // 用资产计价的挂单用的是哪个资产，没有的用原生币计价
+    #[pallet::storage]
+    #[pallet::getter(fn market_assets)]
+    pub type MarketAssets<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, AssetIdOf<T>>;
+
+    // 挂出来给别人配种的kitty：(配种费, 过期区块)
+    #[pallet::storage]
+    #[pallet::getter(fn sire_listings)]
+    pub type SireListings<T: Config> =
+        StorageMap<_, Blake2_128Concat, T::KittyIndex, (BalanceOf<T>, T::BlockNumber)>;
 
+    // 正在拍卖的kitty
     #[pallet::storage]
-    #[pallet::getter(fn owner)]
-    pub type Owner<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<T::AccountId>, ValueQuery>;
+    #[pallet::getter(fn auctions)]
+    pub type Auctions<T: Config> = StorageMap<_, Blake2_128Concat, T::KittyIndex, AuctionOf<T>>;
 
+    // 每个区块结束的拍卖
     #[pallet::storage]
-    #[pallet::getter(fn kitties_market)]
-    pub type KittiesMarket<T: Config> =
-        StorageMap<_, Blake2_128Concat, T::KittyIndex, Option<BalanceOf<T>>, ValueQuery>;
+    #[pallet::getter(fn auctions_ending)]
+    pub type AuctionsEnding<T: Config> = StorageMap<
+        _,
+        Twox64Concat,
+        T::BlockNumber,
+        BoundedVec<T::KittyIndex, T::MaxAuctionsPerBlock>,
+        ValueQuery,
+    >;
 
     #[pallet::event]
     #[pallet::metadata(T::AccountId = "AccountId")]
@@ -69,8 +231,22 @@ pub mod pallet {
         KittyCreate(T::AccountId, T::KittyIndex),
         KittyTransfer(T::AccountId, T::AccountId, T::KittyIndex),
         KittyBreed(T::AccountId, T::KittyIndex),
-        KittyMarket(T::AccountId, T::KittyIndex, BalanceOf<T>),
-        KittyBuy(T::AccountId, T::KittyIndex, BalanceOf<T>),
+        // 价钱用的资产，None是原生币
+        KittyMarket(T::AccountId, T::KittyIndex, BalanceOf<T>, Option<AssetIdOf<T>>),
+        KittyBuy(T::AccountId, T::KittyIndex, BalanceOf<T>, Option<AssetIdOf<T>>),
+        KittyUnlisted(T::AccountId, T::KittyIndex),
+        SiringApproved(T::AccountId, T::KittyIndex, T::AccountId),
+        KittyReleased(T::AccountId, T::KittyIndex),
+        AuctionCreated(T::AccountId, T::KittyIndex, BalanceOf<T>, T::BlockNumber),
+        BidPlaced(T::AccountId, T::KittyIndex, BalanceOf<T>),
+        AuctionExtended(T::KittyIndex, T::BlockNumber),
+        // 没有人出价，或者最高出价的人接不了kitty时，成交的是None
+        AuctionSettled(T::AccountId, T::KittyIndex, Option<(T::AccountId, BalanceOf<T>)>),
+        SireListed(T::AccountId, T::KittyIndex, BalanceOf<T>, T::BlockNumber),
+        SireUnlisted(T::AccountId, T::KittyIndex),
+        // 谁用了哪个kitty配种，生的孩子，付的配种费
+        SireUsed(T::AccountId, T::KittyIndex, T::KittyIndex, BalanceOf<T>),
+        RoyaltyPaid(T::AccountId, T::KittyIndex, BalanceOf<T>),
     }
 
     #[pallet::error]
@@ -81,11 +257,101 @@ pub mod pallet {
         InvalidKittyIndex,
         InvalidMarketPrice,
         PriceTooLow,
+        TooManyKittiesOwned,
+        BuyerIsOwner,
+        KittyOnCooldown,
+        CannotCoverDeposit,
+        KittyInAuction,
+        KittyOnMarket,
+        InvalidAuctionEnd,
+        AuctionQueueFull,
+        AuctionNotFound,
+        AuctionEnded,
+        BidTooLow,
+        IncompatibleGender,
+        InvalidSireExpiry,
+        SireNotListed,
+        SireListingExpired,
+        UnknownAsset,
+        AssetMismatch,
+    }
+
+    #[pallet::genesis_config]
+    pub struct GenesisConfig<T: Config> {
+        // 创世区块里的kitty：(所有者, dna, 市场上的价钱)，价钱是None的不挂单
+        pub kitties: Vec<(T::AccountId, [u8; 16], Option<BalanceOf<T>>)>,
+    }
+
+    #[cfg(feature = "std")]
+    impl<T: Config> Default for GenesisConfig<T> {
+        fn default() -> Self {
+            Self { kitties: Vec::new() }
+        }
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+        fn build(&self) {
+            for (owner, dna, price) in &self.kitties {
+                let kitty_id =
+                    Pallet::<T>::next_kitty_id().expect("genesis kitties overflow KittyIndex");
+                Pallet::<T>::ensure_can_own(owner)
+                    .expect("genesis kitty owner owns more than MaxKittiesOwned");
+
+                // 和创建一样锁定一定的钱
+                let deposit = T::MinimumVotingLock::get();
+                T::Currency::reserve(owner, deposit)
+                    .expect("genesis kitty owner cannot cover the deposit");
+
+                Pallet::<T>::add_one_kitty(owner.clone(), kitty_id, *dna);
+                KittyDeposits::<T>::insert(kitty_id, deposit);
+
+                Pallet::<T>::set_price(kitty_id, *price);
+            }
+
+            StorageVersion::<T>::put(Releases::V2);
+        }
+    }
+
+    #[pallet::hooks]
+    impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+        // 在on_finalize里结算的拍卖的权重先在这里算上，升级时没迁移完的v1 kitty接着迁移
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let settle = match AuctionsEnding::<T>::decode_len(now).unwrap_or(0) as u32 {
+                0 => T::DbWeight::get().reads(1),
+                count => T::WeightInfo::on_finalize(count),
+            };
+            settle.saturating_add(crate::migrations::migrate_to_v2::<T>())
+        }
+
+        // 结算在这个区块结束的拍卖
+        fn on_finalize(now: T::BlockNumber) {
+            for kitty_id in AuctionsEnding::<T>::take(now) {
+                if let Some(auction) = Auctions::<T>::take(kitty_id) {
+                    Self::settle_auction(kitty_id, auction);
+                }
+            }
+        }
+
+        // 把v1分开存的kitty合起来，一次迁移不完的留给之后的区块
+        fn on_runtime_upgrade() -> Weight {
+            crate::migrations::migrate_to_v2::<T>()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<(), &'static str> {
+            crate::migrations::pre_migrate_to_v2::<T>()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade() -> Result<(), &'static str> {
+            crate::migrations::post_migrate_to_v2::<T>()
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        #[pallet::weight(T::WeightInfo::create())]
         pub fn create(origin: OriginFor<T>) -> DispatchResult {
             // 方法调用者
             let sender = ensure_signed(origin)?;
@@ -96,11 +362,16 @@ pub mod pallet {
             // 创建dna
             let dna = Self::random_value(&sender);
 
+            // kitty数量不能超过上限
+            Self::ensure_can_own(&sender)?;
+
             // 锁定一定的钱
-            T::Currency::reserve(&sender, T::MinimumVotingLock::get())?;
+            let deposit = T::MinimumVotingLock::get();
+            T::Currency::reserve(&sender, deposit)?;
 
             // 保存新的kitty
             Self::add_one_kitty(sender.clone(), kitty_id, dna);
+            KittyDeposits::<T>::insert(kitty_id, deposit);
 
             // 事件
             Self::deposit_event(Event::KittyCreate(sender, kitty_id));
@@ -108,7 +379,7 @@ pub mod pallet {
             Ok(())
         }
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        #[pallet::weight(T::WeightInfo::transfer())]
         pub fn transfer(
             origin: OriginFor<T>,
             new_owner: T::AccountId,
@@ -119,12 +390,15 @@ pub mod pallet {
 
             // kitty是调用者的
             ensure!(
-                Some(sender.clone()) == Owner::<T>::get(kitty_id),
+                Some(sender.clone()) == Self::owner(kitty_id),
                 Error::<T>::NotOwner
             );
 
+            // 拍卖中的kitty不能转
+            Self::ensure_not_in_auction(kitty_id)?;
+
             // 变一下kitty所属关系
-            Owner::<T>::insert(kitty_id, Some(new_owner.clone()));
+            Self::change_owner(&sender, &new_owner, kitty_id)?;
 
             // 事件
             Self::deposit_event(Event::KittyTransfer(sender, new_owner, kitty_id));
@@ -132,7 +406,7 @@ pub mod pallet {
             Ok(())
         }
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        #[pallet::weight(T::WeightInfo::breed())]
         pub fn breed(
             origin: OriginFor<T>,
             kitty_id_1: T::KittyIndex,
@@ -141,65 +415,371 @@ pub mod pallet {
             // 方法调用者
             let sender = ensure_signed(origin)?;
 
-            // 获取孩子的id
-            let kitty_id = Self::next_kitty_id()?;
+            // 获取孩子的id，生成孩子的dna
+            let (kitty_id, dna) = Self::prepare_breed(&sender, kitty_id_1, kitty_id_2)?;
 
-            // 生成孩子的dna
-            let dna = Self::breed_dna(&sender, kitty_id_1, kitty_id_2)?;
+            // 父母都要是调用者的，或者所有者允许调用者用，并且都不在冷却期
+            Self::ensure_can_breed(&sender, kitty_id_1)?;
+            Self::ensure_can_breed(&sender, kitty_id_2)?;
 
             // 保存孩子kitty
-            Self::add_one_kitty(sender.clone(), kitty_id, dna);
+            Self::finish_breed(&sender, kitty_id_1, kitty_id_2, kitty_id, dna)
+        }
+
+        #[pallet::weight(T::WeightInfo::list_sire())]
+        pub fn list_sire(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            price: BalanceOf<T>,
+            expires_at: T::BlockNumber,
+        ) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // kitty是调用者的
+            ensure!(
+                Some(sender.clone()) == Self::owner(kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            // 过期区块要在以后
+            ensure!(
+                expires_at > <frame_system::Pallet<T>>::block_number(),
+                Error::<T>::InvalidSireExpiry
+            );
+
+            // 挂出来给别人配种，已经挂了的话覆盖原来的价钱和过期区块
+            SireListings::<T>::insert(kitty_id, (price, expires_at));
+
+            // 事件
+            Self::deposit_event(Event::SireListed(sender, kitty_id, price, expires_at));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::unlist_sire())]
+        pub fn unlist_sire(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // kitty是调用者的
+            ensure!(
+                Some(sender.clone()) == Self::owner(kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            // kitty挂出来配种了
+            ensure!(
+                SireListings::<T>::contains_key(kitty_id),
+                Error::<T>::SireNotListed
+            );
+
+            // 撤下来
+            SireListings::<T>::remove(kitty_id);
+
+            // 事件
+            Self::deposit_event(Event::SireUnlisted(sender, kitty_id));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::breed_with_sire())]
+        #[transactional]
+        pub fn breed_with_sire(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            sire_id: T::KittyIndex,
+            max_fee: BalanceOf<T>,
+        ) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // 配种的kitty挂出来了，并且没过期
+            let (fee, expires_at) =
+                Self::sire_listings(sire_id).ok_or(Error::<T>::SireNotListed)?;
+            ensure!(
+                <frame_system::Pallet<T>>::block_number() < expires_at,
+                Error::<T>::SireListingExpired
+            );
+
+            // 愿意出的钱不能比配种费低
+            ensure!(max_fee >= fee, Error::<T>::PriceTooLow);
+
+            // 不用给自己付配种费
+            let sire_owner = Self::owner(sire_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            ensure!(sire_owner != sender, Error::<T>::BuyerIsOwner);
+
+            // 获取孩子的id，生成孩子的dna
+            let (child_id, dna) = Self::prepare_breed(&sender, kitty_id, sire_id)?;
+
+            // 自己这边的kitty要是调用者的，或者所有者允许调用者用，两个都不在冷却期
+            Self::ensure_can_breed(&sender, kitty_id)?;
+            Self::ensure_off_cooldown(sire_id)?;
+
+            // 配种费付给配种kitty的所有者
+            T::Currency::transfer(&sender, &sire_owner, fee, ExistenceRequirement::KeepAlive)?;
+
+            // 保存孩子kitty，孩子归调用者
+            Self::finish_breed(&sender, kitty_id, sire_id, child_id, dna)?;
 
             // 事件
-            Self::deposit_event(Event::KittyBreed(sender, kitty_id));
+            Self::deposit_event(Event::SireUsed(sender, sire_id, child_id, fee));
 
             Ok(())
         }
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        #[pallet::weight(T::WeightInfo::approve_siring())]
+        pub fn approve_siring(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            approved: T::AccountId,
+        ) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // kitty是调用者的
+            ensure!(
+                Some(sender.clone()) == Self::owner(kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            // 允许approved用这个kitty来生一次
+            SireApprovals::<T>::insert(kitty_id, approved.clone());
+
+            // 事件
+            Self::deposit_event(Event::SiringApproved(sender, kitty_id, approved));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::market())]
         pub fn market(
             origin: OriginFor<T>,
             kitty_id: T::KittyIndex,
             price: BalanceOf<T>,
+            asset_id: Option<AssetIdOf<T>>,
         ) -> DispatchResult {
             // 方法调用者
             let sender = ensure_signed(origin)?;
 
             // kitty是调用者的
             ensure!(
-                Some(sender.clone()) == Owner::<T>::get(kitty_id),
+                Some(sender.clone()) == Self::owner(kitty_id),
                 Error::<T>::NotOwner
             );
 
-            // 把kitty放到市场上
-            KittiesMarket::<T>::insert(kitty_id, Some(price));
+            // 拍卖中的kitty不能再挂单
+            Self::ensure_not_in_auction(kitty_id)?;
+
+            // 资产要存在，存在的资产最小余额不会是0
+            if let Some(asset_id) = asset_id {
+                ensure!(
+                    !<T::Assets as fungibles::Inspect<T::AccountId>>::minimum_balance(asset_id)
+                        .is_zero(),
+                    Error::<T>::UnknownAsset
+                );
+            }
+
+            // 把kitty放到市场上，没有资产的用原生币计价
+            Self::set_price(kitty_id, Some(price));
+            match asset_id {
+                Some(asset_id) => MarketAssets::<T>::insert(kitty_id, asset_id),
+                None => MarketAssets::<T>::remove(kitty_id),
+            }
 
             // 事件
-            Self::deposit_event(Event::KittyMarket(sender, kitty_id, price));
+            Self::deposit_event(Event::KittyMarket(sender, kitty_id, price, asset_id));
 
             Ok(())
         }
 
-        #[pallet::weight(10_000 + T::DbWeight::get().writes(1))]
+        #[pallet::weight(T::WeightInfo::unlist())]
+        pub fn unlist(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // kitty是调用者的
+            ensure!(
+                Some(sender.clone()) == Self::owner(kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            // kitty在市场上
+            ensure!(
+                Self::kitties_market(kitty_id).is_some(),
+                Error::<T>::InvalidMarketPrice
+            );
+
+            // 从市场上撤下来
+            Self::set_price(kitty_id, None);
+
+            // 事件
+            Self::deposit_event(Event::KittyUnlisted(sender, kitty_id));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::release())]
+        pub fn release(origin: OriginFor<T>, kitty_id: T::KittyIndex) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // kitty是调用者的
+            ensure!(
+                Some(sender.clone()) == Self::owner(kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            // 拍卖中的kitty不能删
+            Self::ensure_not_in_auction(kitty_id)?;
+
+            // 删除kitty，退回锁定的钱
+            Self::remove_one_kitty(&sender, kitty_id);
+
+            // 事件
+            Self::deposit_event(Event::KittyReleased(sender, kitty_id));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::create_auction())]
+        pub fn create_auction(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            reserve_price: BalanceOf<T>,
+            min_increment: BalanceOf<T>,
+            end: T::BlockNumber,
+        ) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            // kitty是调用者的
+            ensure!(
+                Some(sender.clone()) == Self::owner(kitty_id),
+                Error::<T>::NotOwner
+            );
+
+            // 不能同时挂单和拍卖
+            Self::ensure_not_in_auction(kitty_id)?;
+            ensure!(
+                Self::kitties_market(kitty_id).is_none(),
+                Error::<T>::KittyOnMarket
+            );
+
+            // 结束区块要在以后
+            ensure!(
+                end > <frame_system::Pallet<T>>::block_number(),
+                Error::<T>::InvalidAuctionEnd
+            );
+
+            // 放到结束区块的队列里
+            Self::enqueue_auction(kitty_id, end)?;
+            Auctions::<T>::insert(
+                kitty_id,
+                Auction {
+                    seller: sender.clone(),
+                    reserve_price,
+                    min_increment,
+                    end,
+                    highest_bid: None,
+                },
+            );
+
+            // 事件
+            Self::deposit_event(Event::AuctionCreated(sender, kitty_id, reserve_price, end));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::bid())]
+        #[transactional]
+        pub fn bid(
+            origin: OriginFor<T>,
+            kitty_id: T::KittyIndex,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            // 方法调用者
+            let sender = ensure_signed(origin)?;
+
+            let mut auction = Self::auctions(kitty_id).ok_or(Error::<T>::AuctionNotFound)?;
+
+            // 结束区块当中就不能再出价了
+            let now = <frame_system::Pallet<T>>::block_number();
+            ensure!(now < auction.end, Error::<T>::AuctionEnded);
+
+            // 不能拍自己的kitty
+            ensure!(sender != auction.seller, Error::<T>::BuyerIsOwner);
+
+            // 第一次出价不低于保留价，后面的出价至少比最高价多一个最小加价
+            match &auction.highest_bid {
+                Some((_, highest)) => ensure!(
+                    amount > *highest && amount >= highest.saturating_add(auction.min_increment),
+                    Error::<T>::BidTooLow
+                ),
+                None => ensure!(amount >= auction.reserve_price, Error::<T>::BidTooLow),
+            }
+
+            // 锁定新的出价，退回原来最高出价的人的钱
+            T::Currency::reserve(&sender, amount)?;
+            if let Some((bidder, highest)) = &auction.highest_bid {
+                T::Currency::unreserve(bidder, *highest);
+            }
+            auction.highest_bid = Some((sender.clone(), amount));
+
+            // 快结束时有人出价，结束区块往后推，那个区块满了就放到之后第一个有空位的区块
+            let extension = T::AuctionExtension::get();
+            if auction.end.saturating_sub(now) < extension {
+                AuctionsEnding::<T>::mutate(auction.end, |queue| queue.retain(|id| *id != kitty_id));
+                let end = Self::enqueue_auction_after(kitty_id, now.saturating_add(extension));
+                auction.end = end;
+                Self::deposit_event(Event::AuctionExtended(kitty_id, end));
+            }
+
+            Auctions::<T>::insert(kitty_id, auction);
+
+            // 事件
+            Self::deposit_event(Event::BidPlaced(sender, kitty_id, amount));
+
+            Ok(())
+        }
+
+        #[pallet::weight(T::WeightInfo::buy())]
+        #[transactional]
         pub fn buy(
             origin: OriginFor<T>,
             kitty_id: T::KittyIndex,
             price: BalanceOf<T>,
+            asset_id: Option<AssetIdOf<T>>,
         ) -> DispatchResult {
             // 方法调用者
             let sender = ensure_signed(origin)?;
 
             // 从市场购买kitty
-            let kitty_price = Self::buy_kitty(&sender, kitty_id, price)?;
+            let kitty_price = Self::buy_kitty(&sender, kitty_id, price, asset_id)?;
 
             // 事件
-            Self::deposit_event(Event::KittyBuy(sender, kitty_id, kitty_price));
+            Self::deposit_event(Event::KittyBuy(sender, kitty_id, kitty_price, asset_id));
 
             Ok(())
         }
     }
 
     impl<T: Config> Pallet<T> {
+        // kitty属于谁
+        pub fn owner(kitty_id: T::KittyIndex) -> Option<T::AccountId> {
+            Self::kitties(kitty_id).map(|kitty| kitty.owner)
+        }
+
+        // kitty在市场上的价钱
+        pub fn kitties_market(kitty_id: T::KittyIndex) -> Option<BalanceOf<T>> {
+            Self::kitties(kitty_id).and_then(|kitty| kitty.price)
+        }
+
+        // 从kitty的dna解出属性，给runtime api用
+        pub fn kitty_attributes(kitty_id: T::KittyIndex) -> Option<KittyAttributes> {
+            Self::kitties(kitty_id).map(|kitty| dna::decode(&kitty.dna))
+        }
+
         // 使用随机数创建一个dna
         fn random_value(sender: &T::AccountId) -> [u8; 16] {
             let payload = (
@@ -230,14 +810,280 @@ pub mod pallet {
 
         // 增加一个kitty
         fn add_one_kitty(owner: T::AccountId, kitty_id: T::KittyIndex, dna: [u8; 16]) {
-            // 保存kitty
-            Kitties::<T>::insert(kitty_id, Some(Kitty(dna)));
-            // 保存kitty属于谁
-            Owner::<T>::insert(kitty_id, Some(owner.clone()));
+            // 保存kitty和kitty属于谁，新的kitty不在市场上
+            Kitties::<T>::insert(kitty_id, KittyInfo { dna, owner: owner.clone(), price: None });
+            Self::add_owned(&owner, kitty_id);
+            // 保存kitty是谁创建的
+            KittyCreators::<T>::insert(kitty_id, &owner);
             // 创建了多少个kitty了
             KittiesCount::<T>::put(kitty_id);
         }
 
+        // 挂单或者撤单，kitty不存在时什么都不做
+        fn set_price(kitty_id: T::KittyIndex, price: Option<BalanceOf<T>>) {
+            Kitties::<T>::mutate(kitty_id, |kitty| {
+                if let Some(kitty) = kitty {
+                    kitty.price = price;
+                }
+            });
+            // 撤单时计价的资产也作废
+            if price.is_none() {
+                MarketAssets::<T>::remove(kitty_id);
+            }
+        }
+
+        // kitty没有在拍卖
+        fn ensure_not_in_auction(kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure!(
+                !Auctions::<T>::contains_key(kitty_id),
+                Error::<T>::KittyInAuction
+            );
+            Ok(())
+        }
+
+        // 把拍卖放到结束区块的队列里
+        fn enqueue_auction(kitty_id: T::KittyIndex, end: T::BlockNumber) -> DispatchResult {
+            AuctionsEnding::<T>::try_mutate(end, |queue| {
+                queue.try_push(kitty_id).map_err(|_| Error::<T>::AuctionQueueFull)
+            })?;
+            Ok(())
+        }
+
+        // 放到从end开始第一个还有空位的区块的队列里，返回实际的结束区块
+        fn enqueue_auction_after(kitty_id: T::KittyIndex, end: T::BlockNumber) -> T::BlockNumber {
+            let mut at = end;
+            while AuctionsEnding::<T>::try_mutate(at, |queue| queue.try_push(kitty_id)).is_err() {
+                at = at.saturating_add(1u32.into());
+            }
+            at
+        }
+
+        // 结算拍卖：最高出价的人付钱拿走kitty，拿不走时把钱退给他，kitty留给卖家
+        fn settle_auction(kitty_id: T::KittyIndex, auction: AuctionOf<T>) {
+            let sold = match auction.highest_bid {
+                Some((bidder, price)) => {
+                    T::Currency::unreserve(&bidder, price);
+                    Self::pay_and_take(&auction.seller, &bidder, price, kitty_id)
+                        .ok()
+                        .map(|_| (bidder, price))
+                }
+                None => None,
+            };
+
+            Self::deposit_event(Event::AuctionSettled(auction.seller, kitty_id, sold));
+        }
+
+        // 买家付钱，kitty转给买家，失败时都不变
+        #[transactional]
+        fn pay_and_take(
+            seller: &T::AccountId,
+            buyer: &T::AccountId,
+            price: BalanceOf<T>,
+            kitty_id: T::KittyIndex,
+        ) -> DispatchResult {
+            Self::pay_for_kitty(buyer, seller, kitty_id, price, None)?;
+            Self::change_owner(seller, buyer, kitty_id)
+        }
+
+        // 买家付钱：创建者不是买卖双方时，按比例分版税给创建者，剩下的给卖家。
+        // 有资产时用资产付，版税也是
+        fn pay_for_kitty(
+            buyer: &T::AccountId,
+            seller: &T::AccountId,
+            kitty_id: T::KittyIndex,
+            price: BalanceOf<T>,
+            asset_id: Option<AssetIdOf<T>>,
+        ) -> DispatchResult {
+            let mut to_seller = price;
+
+            if let Some(creator) = Self::kitty_creators(kitty_id) {
+                let royalty = T::CreatorRoyalty::get() * price;
+                let third_party = &creator != seller && &creator != buyer;
+                // 创建者收不了版税时（比如账户已经没了，版税又不够存在押金），版税也给卖家
+                if third_party &&
+                    !royalty.is_zero() &&
+                    Self::pay(asset_id, buyer, &creator, royalty).is_ok()
+                {
+                    to_seller = price.saturating_sub(royalty);
+                    Self::deposit_event(Event::RoyaltyPaid(creator, kitty_id, royalty));
+                }
+            }
+
+            Self::pay(asset_id, buyer, seller, to_seller)
+        }
+
+        // 转钱，没有资产的转原生币，都要给付钱的人留下存在押金
+        fn pay(
+            asset_id: Option<AssetIdOf<T>>,
+            from: &T::AccountId,
+            to: &T::AccountId,
+            amount: BalanceOf<T>,
+        ) -> DispatchResult {
+            match asset_id {
+                Some(asset_id) => {
+                    <T::Assets as fungibles::Transfer<_>>::transfer(asset_id, from, to, amount, true)
+                        .map(|_| ())
+                }
+                None => T::Currency::transfer(from, to, amount, ExistenceRequirement::KeepAlive),
+            }
+        }
+
+        // 删除一个kitty，把锁定的钱退给所有者
+        fn remove_one_kitty(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+            Kitties::<T>::remove(kitty_id);
+            Self::remove_owned(owner, kitty_id);
+            MarketAssets::<T>::remove(kitty_id);
+            SireListings::<T>::remove(kitty_id);
+            SireApprovals::<T>::remove(kitty_id);
+            NextBreedAt::<T>::remove(kitty_id);
+            KittyParents::<T>::remove(kitty_id);
+            KittyGeneration::<T>::remove(kitty_id);
+            KittyCreators::<T>::remove(kitty_id);
+
+            let deposit = KittyDeposits::<T>::take(kitty_id);
+            T::Currency::unreserve(owner, deposit);
+        }
+
+        // 调用者可以用这个kitty来生：是自己的或者所有者允许了，并且不在冷却期
+        fn ensure_can_breed(sender: &T::AccountId, kitty_id: T::KittyIndex) -> DispatchResult {
+            let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
+            ensure!(
+                &owner == sender || Self::sire_approvals(kitty_id).as_ref() == Some(sender),
+                Error::<T>::NotOwner
+            );
+
+            Self::ensure_off_cooldown(kitty_id)
+        }
+
+        // kitty不在冷却期
+        fn ensure_off_cooldown(kitty_id: T::KittyIndex) -> DispatchResult {
+            ensure!(
+                <frame_system::Pallet<T>>::block_number() >= Self::next_breed_at(kitty_id),
+                Error::<T>::KittyOnCooldown
+            );
+            Ok(())
+        }
+
+        // 获取孩子的id，生成孩子的dna
+        fn prepare_breed(
+            sender: &T::AccountId,
+            kitty_id_1: T::KittyIndex,
+            kitty_id_2: T::KittyIndex,
+        ) -> sp_std::result::Result<(T::KittyIndex, [u8; 16]), DispatchError> {
+            let kitty_id = Self::next_kitty_id()?;
+            let dna = Self::breed_dna(sender, kitty_id_1, kitty_id_2)?;
+            Ok((kitty_id, dna))
+        }
+
+        // 保存孩子kitty，记下父母和代数，父母进入冷却期
+        fn finish_breed(
+            sender: &T::AccountId,
+            kitty_id_1: T::KittyIndex,
+            kitty_id_2: T::KittyIndex,
+            kitty_id: T::KittyIndex,
+            dna: [u8; 16],
+        ) -> DispatchResult {
+            // kitty数量不能超过上限
+            Self::ensure_can_own(sender)?;
+
+            // 和创建一样锁定一定的钱
+            let deposit = T::MinimumVotingLock::get();
+            T::Currency::reserve(sender, deposit)?;
+
+            // 保存孩子kitty
+            Self::add_one_kitty(sender.clone(), kitty_id, dna);
+            KittyDeposits::<T>::insert(kitty_id, deposit);
+
+            // 保存孩子的父母和代数
+            let generation = Self::kitty_generation(kitty_id_1)
+                .max(Self::kitty_generation(kitty_id_2))
+                .saturating_add(1);
+            KittyParents::<T>::insert(kitty_id, (kitty_id_1, kitty_id_2));
+            KittyGeneration::<T>::insert(kitty_id, generation);
+
+            // 父母进入冷却期，允许别人用的授权作废
+            Self::start_cooldown(kitty_id_1);
+            Self::start_cooldown(kitty_id_2);
+
+            // 事件
+            Self::deposit_event(Event::KittyBreed(sender.clone(), kitty_id));
+
+            Ok(())
+        }
+
+        // 生完以后进入冷却期，授权作废
+        fn start_cooldown(kitty_id: T::KittyIndex) {
+            let now = <frame_system::Pallet<T>>::block_number();
+            NextBreedAt::<T>::insert(kitty_id, now.saturating_add(T::BreedingCooldown::get()));
+            SireApprovals::<T>::remove(kitty_id);
+        }
+
+        // 账户的kitty数量没到上限
+        fn ensure_can_own(owner: &T::AccountId) -> DispatchResult {
+            ensure!(
+                Self::owned_kitties_count(owner) < T::MaxKittiesOwned::get(),
+                Error::<T>::TooManyKittiesOwned
+            );
+            Ok(())
+        }
+
+        // 把kitty加到账户的索引里
+        pub(crate) fn add_owned(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+            OwnedKitties::<T>::insert(owner, kitty_id, ());
+            OwnedKittiesCount::<T>::mutate(owner, |count| *count = count.saturating_add(1));
+        }
+
+        // 把kitty从账户的索引里拿掉
+        fn remove_owned(owner: &T::AccountId, kitty_id: T::KittyIndex) {
+            OwnedKitties::<T>::remove(owner, kitty_id);
+            OwnedKittiesCount::<T>::mutate(owner, |count| *count = count.saturating_sub(1));
+        }
+
+        // 变一下kitty所属关系，同时更新两个账户的索引
+        fn change_owner(
+            from: &T::AccountId,
+            to: &T::AccountId,
+            kitty_id: T::KittyIndex,
+        ) -> DispatchResult {
+            // 转给自己时什么都不变
+            if from == to {
+                return Ok(());
+            }
+
+            Self::ensure_can_own(to)?;
+
+            // 锁定的钱跟着kitty走：新所有者先锁定，锁定成功再退给原来的所有者
+            let deposit = Self::kitty_deposits(kitty_id);
+            T::Currency::reserve(to, deposit).map_err(|_| Error::<T>::CannotCoverDeposit)?;
+            T::Currency::unreserve(from, deposit);
+
+            Self::remove_owned(from, kitty_id);
+            Self::add_owned(to, kitty_id);
+
+            // 换所有者，原来的所有者挂的价钱作废
+            let listed = Kitties::<T>::mutate(kitty_id, |kitty| match kitty {
+                Some(kitty) => {
+                    kitty.owner = to.clone();
+                    kitty.price.take().is_some()
+                }
+                None => false,
+            });
+
+            // 原来的所有者给的授权作废
+            SireApprovals::<T>::remove(kitty_id);
+
+            // 原来的所有者挂的单作废
+            if listed {
+                MarketAssets::<T>::remove(kitty_id);
+                Self::deposit_event(Event::KittyUnlisted(from.clone(), kitty_id));
+            }
+            if SireListings::<T>::take(kitty_id).is_some() {
+                Self::deposit_event(Event::SireUnlisted(from.clone(), kitty_id));
+            }
+
+            Ok(())
+        }
+
         // 生成孩子的dna
         fn breed_dna(
             sender: &T::AccountId,
@@ -252,8 +1098,14 @@ pub mod pallet {
             let kitty2 = Self::kitties(kitty_id_2).ok_or(Error::<T>::InvalidKittyIndex)?;
 
             // 父母的dna
-            let dna_1 = kitty1.0;
-            let dna_2 = kitty2.0;
+            let dna_1 = kitty1.dna;
+            let dna_2 = kitty2.dna;
+
+            // 父母要一公一母
+            ensure!(
+                dna::decode(&dna_1).gender != dna::decode(&dna_2).gender,
+                Error::<T>::IncompatibleGender
+            );
 
             // 通过父母的dna生成孩子的dna
             let selector = Self::random_value(sender);
@@ -266,30 +1118,38 @@ pub mod pallet {
             Ok(new_dna)
         }
 
-        // 从市场购买kitty
+        // 从市场购买kitty，买家给出的资产要和挂单的一样
         fn buy_kitty(
             sender: &T::AccountId,
             kitty_id: T::KittyIndex,
             price: BalanceOf<T>,
+            asset_id: Option<AssetIdOf<T>>,
         ) -> sp_std::result::Result<BalanceOf<T>, DispatchError> {
             // 获取kitty所属用户，判断kitty是否存在
             let owner = Self::owner(kitty_id).ok_or(Error::<T>::InvalidKittyIndex)?;
 
+            // 不能买自己的kitty
+            ensure!(&owner != sender, Error::<T>::BuyerIsOwner);
+
             // 市场上kitty挂的价钱，判断kitty是否在市场上挂单
             let kitty_price =
                 Self::kitties_market(kitty_id).ok_or(Error::<T>::InvalidMarketPrice)?;
 
-            // 出的钱要比市场上的价钱高
+            // 出的钱要比市场上的价钱高，用的是挂单的资产
             ensure!(price >= kitty_price, Error::<T>::PriceTooLow);
+            ensure!(asset_id == Self::market_assets(kitty_id), Error::<T>::AssetMismatch);
+
+            // 买家的kitty数量不能超过上限
+            Self::ensure_can_own(sender)?;
 
-            // 转钱，把钱直接转给在市场上挂单卖的人
-            T::Currency::transfer(sender, &owner, kitty_price, ExistenceRequirement::KeepAlive)?;
+            // 转钱，把钱直接转给在市场上挂单卖的人，创建者分一部分版税
+            Self::pay_for_kitty(sender, &owner, kitty_id, kitty_price, asset_id)?;
 
             // 从市场上撤下来
-            KittiesMarket::<T>::remove(kitty_id);
+            Self::set_price(kitty_id, None);
 
             // 变一下kitty所属关系
-            Owner::<T>::insert(kitty_id, Some(sender.clone()));
+            Self::change_owner(&owner, sender, kitty_id)?;
 
             Ok(kitty_price)
         }
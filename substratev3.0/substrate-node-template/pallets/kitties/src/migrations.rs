@@ -0,0 +1,234 @@
+//! 存储迁移：把 v1 分开存的 dna、所有者和价钱合成一条 `KittyInfo`。
+//!
+//! v1 的 `Kitties`、`Owner`、`KittiesMarket` 都是值为 `Option<...>` 的 `ValueQuery`，
+//! 键都是 blake2_128_concat 的 kitty id。v2 只留下 `Kitties`，值是 [`KittyInfo`]，
+//! 没有所有者的 kitty 用不了，迁移时直接丢掉。
+//!
+//! v1 没有所有者索引、创建者和押金记录，迁移时补上：kitty 记到所有者的索引里，创建者记为现在的所有者。
+//! v1 创建 kitty 时从创建者锁定了 `MinimumVotingLock`，但没有记在哪个 kitty 上，也从来不释放。
+//! 所有者被锁定的钱里还有没记在任何 kitty 上的一份押金时，迁移直接把它记成这个 kitty 的押金，不再另外锁定；
+//! 没有的（比如 kitty 是别人创建后转过来的）按 v2 的规则从所有者锁定押金，钱不够的押金记为 0，删除 kitty 时也不返还。
+//!
+//! kitty 可能很多，一个区块最多迁移 [`MIGRATION_BATCH`] 个：按存储键的顺序往后读 v1 的 kitty，
+//! 读到哪里记在迁移进度里，升级时迁移第一批，剩下的在之后每个区块的 `on_initialize` 里接着迁移，
+//! 全部迁移完才删掉 v1 剩下的所有者和价钱，把版本改成 V2。迁移期间新建的 kitty 已经有创建者，会被跳过。
+
+use crate::{
+    BalanceOf, Config, Kitties, KittyCreators, KittyDeposits, KittyInfo, OwnedKitties, Pallet,
+    Releases, StorageVersion,
+};
+use codec::Decode;
+use frame_support::{
+    storage::{
+        migration::{
+            get_storage_value, put_storage_value, remove_storage_prefix, take_storage_item,
+            take_storage_value,
+        },
+        unhashed,
+    },
+    traits::{Get, PalletInfoAccess, ReservableCurrency},
+    weights::Weight,
+    Blake2_128Concat, StorageHasher, Twox128,
+};
+use sp_runtime::traits::{Saturating, Zero};
+use sp_std::vec::Vec;
+
+#[cfg(any(feature = "try-runtime", test))]
+use crate::OwnedKittiesCount;
+#[cfg(feature = "try-runtime")]
+use codec::Encode;
+#[cfg(any(feature = "try-runtime", test))]
+use frame_support::storage::migration::storage_iter;
+#[cfg(feature = "try-runtime")]
+use frame_support::storage::migration::storage_key_iter;
+
+/// 一个区块最多迁移的 v1 kitty 数
+pub const MIGRATION_BATCH: usize = 64;
+
+// v1 的存储名，模块前缀和 v2 一样
+const V1_KITTIES: &[u8] = b"Kitties";
+const V1_OWNER: &[u8] = b"Owner";
+const V1_MARKET: &[u8] = b"KittiesMarket";
+
+// 迁移进度：最后读过的 v1 kitty 的存储键
+const MIGRATION_CURSOR: &[u8] = b"MigrationCursor";
+
+fn module_prefix<T: Config>() -> &'static [u8] {
+    <Pallet<T> as PalletInfoAccess>::name().as_bytes()
+}
+
+// v1 和 v2 的 kitty 共用的存储键前缀
+fn kitties_prefix<T: Config>() -> Vec<u8> {
+    let mut prefix = Twox128::hash(module_prefix::<T>()).to_vec();
+    prefix.extend_from_slice(&Twox128::hash(V1_KITTIES));
+    prefix
+}
+
+// v1 的 kitty：(kitty id, dna)，解码失败的会被跳过
+#[cfg(feature = "try-runtime")]
+fn v1_kitties<T: Config>() -> Vec<(T::KittyIndex, Option<[u8; 16]>)> {
+    storage_key_iter::<T::KittyIndex, Option<[u8; 16]>, Blake2_128Concat>(
+        module_prefix::<T>(),
+        V1_KITTIES,
+    )
+    .collect()
+}
+
+// 所有者被锁定的钱减去已经记在他的 kitty 上的押金，还够一份押金时直接接过来；
+// 不够时从所有者重新锁定，钱不够的押金为 0
+fn take_over_deposit<T: Config>(owner: &T::AccountId) -> BalanceOf<T> {
+    let deposit = T::MinimumVotingLock::get();
+    let attributed = OwnedKitties::<T>::iter_prefix(owner)
+        .fold(BalanceOf::<T>::zero(), |sum, (kitty_id, _)| {
+            sum.saturating_add(KittyDeposits::<T>::get(kitty_id))
+        });
+
+    if T::Currency::reserved_balance(owner) >= attributed.saturating_add(deposit) ||
+        T::Currency::reserve(owner, deposit).is_ok()
+    {
+        deposit
+    } else {
+        Zero::zero()
+    }
+}
+
+/// 把最多 [`MIGRATION_BATCH`] 个 v1 的 kitty 合成 `KittyInfo`，全部迁移完后版本改成 V2，
+/// 已经是 v2 时什么都不做
+pub fn migrate_to_v2<T: Config>() -> Weight {
+    if StorageVersion::<T>::get() != Releases::V1 {
+        return T::DbWeight::get().reads(1);
+    }
+
+    let module = module_prefix::<T>();
+    let prefix = kitties_prefix::<T>();
+    let mut cursor = get_storage_value::<Vec<u8>>(module, MIGRATION_CURSOR, &[])
+        .unwrap_or_else(|| prefix.clone());
+
+    let mut count: Weight = 0;
+    let mut moved: Weight = 0;
+    let mut done = false;
+    while count < MIGRATION_BATCH as Weight {
+        let key = match sp_io::storage::next_key(&cursor).filter(|key| key.starts_with(&prefix)) {
+            Some(key) => key,
+            None => {
+                done = true;
+                break;
+            }
+        };
+        cursor = key.clone();
+        count += 1;
+
+        // 键的最后是 blake2_128_concat 的 kitty id
+        let kitty_id = match key
+            .get(prefix.len() + 16..)
+            .and_then(|mut id| T::KittyIndex::decode(&mut id).ok())
+        {
+            Some(kitty_id) => kitty_id,
+            None => continue,
+        };
+
+        // 已经迁移过或者迁移期间新建的 kitty
+        if KittyCreators::<T>::contains_key(kitty_id) {
+            continue;
+        }
+
+        let dna = unhashed::get::<Option<[u8; 16]>>(&key).flatten();
+        let owner = take_storage_item::<_, Option<T::AccountId>, Blake2_128Concat>(
+            module, V1_OWNER, kitty_id,
+        )
+        .flatten();
+        let price = take_storage_item::<_, Option<BalanceOf<T>>, Blake2_128Concat>(
+            module, V1_MARKET, kitty_id,
+        )
+        .flatten();
+
+        match (dna, owner) {
+            (Some(dna), Some(owner)) => {
+                let deposit = take_over_deposit::<T>(&owner);
+                if !deposit.is_zero() {
+                    KittyDeposits::<T>::insert(kitty_id, deposit);
+                }
+
+                Pallet::<T>::add_owned(&owner, kitty_id);
+                KittyCreators::<T>::insert(kitty_id, &owner);
+                Kitties::<T>::insert(kitty_id, KittyInfo { dna, owner, price });
+                moved += 1;
+            }
+            _ => unhashed::kill(&key),
+        }
+    }
+
+    if done {
+        // 没有对应 kitty 的所有者和价钱也删掉
+        remove_storage_prefix(module, V1_OWNER, &[]);
+        remove_storage_prefix(module, V1_MARKET, &[]);
+        take_storage_value::<Vec<u8>>(module, MIGRATION_CURSOR, &[]);
+        StorageVersion::<T>::put(Releases::V2);
+    } else {
+        put_storage_value(module, MIGRATION_CURSOR, &[], cursor);
+    }
+
+    T::DbWeight::get().reads_writes(
+        2 + 5 * count + (2 + T::MaxKittiesOwned::get() as Weight) * moved,
+        2 + 3 * count + 6 * moved,
+    )
+}
+
+/// 迁移前的检查：v1 的 kitty 都能解码，并且都有所有者
+#[cfg(feature = "try-runtime")]
+pub fn pre_migrate_to_v2<T: Config>() -> Result<(), &'static str> {
+    if StorageVersion::<T>::get() != Releases::V1 {
+        return Ok(());
+    }
+
+    // 值解码成 () 总是成功，用来数 v1 里一共有多少个 kitty
+    let kitties = v1_kitties::<T>();
+    let total = storage_iter::<()>(module_prefix::<T>(), V1_KITTIES).count();
+    frame_support::ensure!(kitties.len() == total, "undecodable v1 kitties");
+
+    for (kitty_id, _) in kitties {
+        let owner = get_storage_value::<Option<T::AccountId>>(
+            module_prefix::<T>(),
+            V1_OWNER,
+            &Blake2_128Concat::hash(&kitty_id.encode()),
+        );
+        frame_support::ensure!(owner.flatten().is_some(), "v1 kitty without an owner");
+    }
+
+    Ok(())
+}
+
+/// 迁移后的检查：迁移完成时 v1 的所有者和价钱都被删掉了，每个 kitty 都在所有者的索引里并且有创建者，
+/// 每个账户的 kitty 数和索引一致
+#[cfg(any(feature = "try-runtime", test))]
+pub fn post_migrate_to_v2<T: Config>() -> Result<(), &'static str> {
+    // 还没迁移完时 v1 的存储还在，只检查已经迁移的 kitty
+    if StorageVersion::<T>::get() == Releases::V2 {
+        for item in &[V1_OWNER, V1_MARKET] {
+            frame_support::ensure!(
+                storage_iter::<()>(module_prefix::<T>(), item).next().is_none(),
+                "v1 storage left after migration"
+            );
+        }
+    }
+
+    for (kitty_id, kitty) in Kitties::<T>::iter() {
+        frame_support::ensure!(
+            OwnedKitties::<T>::contains_key(&kitty.owner, kitty_id),
+            "kitty missing from the owner index"
+        );
+        frame_support::ensure!(
+            KittyCreators::<T>::contains_key(kitty_id),
+            "kitty without a creator"
+        );
+    }
+
+    for (owner, count) in OwnedKittiesCount::<T>::iter() {
+        frame_support::ensure!(
+            OwnedKitties::<T>::iter_prefix(&owner).count() as u32 == count,
+            "owned kitties count out of sync"
+        );
+    }
+
+    Ok(())
+}
@@ -5,6 +5,7 @@ use sp_core::H256;
 use sp_runtime::{
     testing::Header,
     traits::{BlakeTwo256, IdentityLookup},
+    Permill,
 };
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -21,7 +22,8 @@ frame_support::construct_runtime!(
         System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
         RandomnessCollectiveFlip: pallet_randomness_collective_flip::{Pallet, Storage},
         BalancesModule: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
-        KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+        Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
+        KittiesModule: pallet_kitties::{Pallet, Call, Storage, Config<T>, Event<T>},
     }
 );
 
@@ -30,6 +32,16 @@ parameter_types! {
     pub const SS58Prefix: u8 = 42;
     pub const ExistentialDeposit: u64 = 1;
     pub const MinimumVotingLock: u64 = 100;
+    pub const MaxKittiesOwned: u32 = 3;
+    pub const BreedingCooldown: u64 = 5;
+    pub const AuctionExtension: u64 = 3;
+    pub const MaxAuctionsPerBlock: u32 = 2;
+    pub const CreatorRoyalty: Permill = Permill::from_percent(10);
+    pub const AssetDeposit: u64 = 1;
+    pub const ApprovalDeposit: u64 = 1;
+    pub const StringLimit: u32 = 50;
+    pub const MetadataDepositBase: u64 = 1;
+    pub const MetadataDepositPerByte: u64 = 1;
 }
 
 
@@ -45,6 +57,22 @@ impl pallet_balances::Config for Test {
     type ReserveIdentifier = [u8; 8];
 }
 
+impl pallet_assets::Config for Test {
+    type Event = Event;
+    type Balance = u64;
+    type AssetId = u32;
+    type Currency = BalancesModule;
+    type ForceOrigin = frame_system::EnsureRoot<u64>;
+    type AssetDeposit = AssetDeposit;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type ApprovalDeposit = ApprovalDeposit;
+    type StringLimit = StringLimit;
+    type Freezer = ();
+    type Extra = ();
+    type WeightInfo = ();
+}
+
 impl system::Config for Test {
     type BaseCallFilter = frame_support::traits::AllowAll;
     type BlockWeights = ();
@@ -78,6 +106,13 @@ impl pallet_kitties::Config for Test {
     type Currency= BalancesModule;
     type KittyIndex= u32;
     type MinimumVotingLock = MinimumVotingLock;
+    type MaxKittiesOwned = MaxKittiesOwned;
+    type BreedingCooldown = BreedingCooldown;
+    type AuctionExtension = AuctionExtension;
+    type MaxAuctionsPerBlock = MaxAuctionsPerBlock;
+    type CreatorRoyalty = CreatorRoyalty;
+    type Assets = Assets;
+    type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
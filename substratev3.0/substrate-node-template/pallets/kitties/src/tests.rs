@@ -1,8 +1,36 @@
 use super::*;
 use super::{Event as KittyEvent};
-use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use crate::{
+    dna::{self, Color, Gender, KittyAttributes, Pattern, Rarity},
+    mock::*,
+    Error,
+};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    storage::migration::{get_storage_value, put_storage_value},
+    traits::{OnFinalize, OnInitialize, OnRuntimeUpgrade, ReservableCurrency},
+    Blake2_128Concat, StorageHasher,
+};
 use pallet_balances::Error as BalancesError;
+use sp_runtime::BuildStorage;
+
+// 一个区块一个区块地往前走，结束当前区块，开始下一个区块
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        KittiesModule::on_finalize(System::block_number());
+        System::set_block_number(System::block_number() + 1);
+        KittiesModule::on_initialize(System::block_number());
+    }
+}
+
+// 同一个区块里同一个用户创建的kitty的dna一样，性别也一样。
+// 用户1创建kitty1（母），用户2创建kitty2（公）转给用户1，用户1就有了一公一母
+fn create_couple() {
+    assert_ok!(KittiesModule::create(Origin::signed(1)));
+    assert_ok!(KittiesModule::create(Origin::signed(2)));
+    assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 2));
+}
 
 // 创建新的kitty
 #[test]
@@ -13,10 +41,17 @@ fn create_works() {
         assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
         // 创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
-        // kitty的dna测试
-        assert_eq!(Kitties::<Test>::get(1), Some(Kitty([215, 75, 66, 60, 234, 156, 146, 62, 247, 65, 230, 205, 192, 2, 31, 70])));
+        // kitty的dna测试，新的kitty没有挂单
+        assert_eq!(
+            Kitties::<Test>::get(1),
+            Some(KittyInfo {
+                dna: [215, 75, 66, 60, 234, 156, 146, 62, 247, 65, 230, 205, 192, 2, 31, 70],
+                owner: 1,
+                price: None,
+            })
+        );
         // kitty属于用于1
-        assert_eq!(Owner::<Test>::get(1), Some(1));
+        assert_eq!(KittiesModule::owner(1), Some(1));
         // kitty数量是1
         assert_eq!(KittiesCount::<Test>::get(), Some(1));
 
@@ -57,16 +92,17 @@ fn create_failed_when_kitty_index_limit_over() {
 fn transfer_works() {
     new_test_ext().execute_with(|| {
         System::set_block_number(10);
-        // 给用户1钱包放点钱
+        // 给用户1、2钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
         // 先创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 转移
         assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
         // kitty属于用于2
-        assert_eq!(Owner::<Test>::get(1), Some(2));
+        assert_eq!(KittiesModule::owner(1), Some(2));
         // kitty的dna不变
-        assert_eq!(Kitties::<Test>::get(1), Some(Kitty([215, 75, 66, 60, 234, 156, 146, 62, 247, 65, 230, 205, 192, 2, 31, 70])));
+        assert_eq!(Kitties::<Test>::get(1).map(|kitty| kitty.dna), Some([215, 75, 66, 60, 234, 156, 146, 62, 247, 65, 230, 205, 192, 2, 31, 70]));
         // 总数量还是1个
         assert_eq!(KittiesCount::<Test>::get(), Some(1));
 
@@ -99,18 +135,22 @@ fn breed_works() {
         System::set_block_number(10);
         // 给用户1钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
-        // 给用户2钱包放点钱
+        // 给用户2、3钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
         // 用户1创建一个kitty1
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 用户2创建一个kitty2
         assert_ok!(KittiesModule::create(Origin::signed(2)));
+        // 用户1和用户2允许用户3用自己的kitty
+        assert_ok!(KittiesModule::approve_siring(Origin::signed(1), 1, 3));
+        assert_ok!(KittiesModule::approve_siring(Origin::signed(2), 2, 3));
         // 用户3用kitty1和kitty2生成kitty3
         assert_ok!(KittiesModule::breed(Origin::signed(3), 1, 2));
         // kitty属于用于3
-        assert_eq!(Owner::<Test>::get(3), Some(3));
+        assert_eq!(KittiesModule::owner(3), Some(3));
         // kitty的dna不变
-        assert_eq!(Kitties::<Test>::get(3), Some(Kitty([221, 159, 35, 52, 178, 136, 42, 59, 222, 76, 238, 149, 192, 19, 55, 82])));
+        assert_eq!(Kitties::<Test>::get(3).map(|kitty| kitty.dna), Some([221, 159, 35, 52, 178, 136, 42, 59, 222, 76, 238, 149, 192, 19, 55, 82]));
         // 总数量还是3个
         assert_eq!(KittiesCount::<Test>::get(), Some(3));
 
@@ -194,14 +234,14 @@ fn market_works() {
         // 创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 以10的价钱放在市场上卖
-        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
         // kitty在市场上的价钱
-        assert_eq!(KittiesMarket::<Test>::get(1), Some(10));
+        assert_eq!(KittiesModule::kitties_market(1), Some(10));
 
         // 判断事件
         assert!(!System::events().is_empty());
         assert_eq!(System::events().last().expect("events expected").event,
-                   KittyEvent::KittyMarket(1, 1, 10).into()
+                   KittyEvent::KittyMarket(1, 1, 10, None).into()
         );
     })
 }
@@ -216,7 +256,7 @@ fn market_failed_when_kitty_not_self() {
         // 先创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 用户2把用户1的kitty以10的价钱放在市场上卖
-        assert_noop!(KittiesModule::market(Origin::signed(2), 1, 10), Error::<Test>::NotOwner);
+        assert_noop!(KittiesModule::market(Origin::signed(2), 1, 10, None), Error::<Test>::NotOwner);
     })
 }
 
@@ -230,18 +270,18 @@ fn buy_works() {
         // 创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 以10的价钱放在市场上卖
-        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
         // 给用户2钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
         // 用户2买
-        assert_ok!(KittiesModule::buy(Origin::signed(2), 1, 10));
+        assert_ok!(KittiesModule::buy(Origin::signed(2), 1, 10, None));
         // kitty属于用于2
-        assert_eq!(Owner::<Test>::get(1), Some(2));
+        assert_eq!(KittiesModule::owner(1), Some(2));
 
         // 判断事件
         assert!(!System::events().is_empty());
         assert_eq!(System::events().last().expect("events expected").event,
-                   KittyEvent::KittyBuy(2, 1, 10).into()
+                   KittyEvent::KittyBuy(2, 1, 10, None).into()
         );
     })
 }
@@ -256,9 +296,9 @@ fn buy_failed_when_have_no_money() {
         // 创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 以10的价钱放在市场上卖
-        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
         // 用户2买，钱不够
-        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 10), BalancesError::<Test>::InsufficientBalance);
+        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 10, None), BalancesError::<Test>::InsufficientBalance);
     })
 }
 
@@ -272,11 +312,11 @@ fn buy_failed_when_invalid_account_id() {
         // 创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 以10的价钱放在市场上卖
-        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
         // 给用户2钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
         // 用户2买
-        assert_noop!(KittiesModule::buy(Origin::signed(2), 2, 10), Error::<Test>::InvalidKittyIndex);
+        assert_noop!(KittiesModule::buy(Origin::signed(2), 2, 10, None), Error::<Test>::InvalidKittyIndex);
     })
 }
 
@@ -292,7 +332,7 @@ fn buy_failed_when_invalid_market_price() {
         // 给用户2钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
         // 用户2买
-        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 10), Error::<Test>::InvalidMarketPrice);
+        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 10, None), Error::<Test>::InvalidMarketPrice);
     })
 }
 
@@ -306,10 +346,1099 @@ fn buy_failed_when_price_too_low() {
         // 创建一个kitty
         assert_ok!(KittiesModule::create(Origin::signed(1)));
         // 以10的价钱放在市场上卖
-        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
         // 给用户2钱包放点钱
         assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
         // 用户2买
-        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 5), Error::<Test>::PriceTooLow);
+        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 5, None), Error::<Test>::PriceTooLow);
+    })
+}
+
+// 创建、转移和购买kitty时，所有者索引跟着变
+#[test]
+fn owned_kitties_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、3钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        // 用户1创建kitty1和kitty2
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert!(OwnedKitties::<Test>::contains_key(1, 1));
+        assert!(OwnedKitties::<Test>::contains_key(1, 2));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 2);
+
+        // 把kitty2转给用户2
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 2));
+        assert!(!OwnedKitties::<Test>::contains_key(1, 2));
+        assert!(OwnedKitties::<Test>::contains_key(2, 2));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 1);
+        assert_eq!(OwnedKittiesCount::<Test>::get(2), 1);
+
+        // 用户3从市场上买kitty1
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        assert_ok!(KittiesModule::buy(Origin::signed(3), 1, 10, None));
+        assert!(!OwnedKitties::<Test>::contains_key(1, 1));
+        assert!(OwnedKitties::<Test>::contains_key(3, 1));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 0);
+        assert_eq!(OwnedKittiesCount::<Test>::get(3), 1);
+    })
+}
+
+// 生成的kitty加到生成者的索引里
+#[test]
+fn breed_adds_owned_kitty() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_couple();
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+        assert!(OwnedKitties::<Test>::contains_key(1, 3));
+        // 生的kitty的创建者是生的人
+        assert_eq!(KittyCreators::<Test>::get(3), Some(1));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 3);
+    })
+}
+
+// kitty数量到上限以后不能再创建和生成
+#[test]
+fn create_and_breed_failed_when_too_many_kitties_owned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_couple();
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::create(Origin::signed(1)), Error::<Test>::TooManyKittiesOwned);
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 1, 2), Error::<Test>::TooManyKittiesOwned);
+    })
+}
+
+// 接收者的kitty数量到上限以后不能再转给他或者卖给他
+#[test]
+fn transfer_and_buy_failed_when_too_many_kitties_owned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 4), Error::<Test>::TooManyKittiesOwned);
+
+        assert_ok!(KittiesModule::market(Origin::signed(1), 4, 10, None));
+        assert_noop!(KittiesModule::buy(Origin::signed(2), 4, 10, None), Error::<Test>::TooManyKittiesOwned);
+    })
+}
+
+// 把kitty从市场上撤下来
+#[test]
+fn unlist_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        // 撤下来
+        assert_ok!(KittiesModule::unlist(Origin::signed(1), 1));
+        assert_eq!(KittiesModule::kitties_market(1), None);
+
+        // 判断事件
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::KittyUnlisted(1, 1).into()
+        );
+    })
+}
+
+// 撤下其它人的kitty，或者没在市场上的kitty
+#[test]
+fn unlist_failed_when_not_owner_or_not_listed() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        // 没在市场上
+        assert_noop!(KittiesModule::unlist(Origin::signed(1), 1), Error::<Test>::InvalidMarketPrice);
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        // 用户2撤用户1的kitty
+        assert_noop!(KittiesModule::unlist(Origin::signed(2), 1), Error::<Test>::NotOwner);
+    })
+}
+
+// 转移kitty时，原来的挂单作废，不能再按旧价钱买
+#[test]
+fn transfer_clears_listing() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、3钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        // 转给用户2
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        assert_eq!(KittiesModule::kitties_market(1), None);
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::KittyUnlisted(1, 1).into()));
+        // 用户3不能再买
+        assert_noop!(KittiesModule::buy(Origin::signed(3), 1, 10, None), Error::<Test>::InvalidMarketPrice);
+        assert_eq!(KittiesModule::owner(1), Some(2));
+    })
+}
+
+// 不能买自己挂在市场上的kitty
+#[test]
+fn buy_failed_when_buyer_is_owner() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        assert_noop!(KittiesModule::buy(Origin::signed(1), 1, 10, None), Error::<Test>::BuyerIsOwner);
+    })
+}
+
+// 用别人的kitty来生，没有得到允许
+#[test]
+fn breed_failed_when_parent_not_owned() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 1, 2), Error::<Test>::NotOwner);
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 2, 1), Error::<Test>::NotOwner);
+        // 不是自己的kitty不能允许别人用
+        assert_noop!(KittiesModule::approve_siring(Origin::signed(1), 2, 1), Error::<Test>::NotOwner);
+    })
+}
+
+// 允许别人用自己的kitty来生，用一次就作废，kitty转移以后也作废
+#[test]
+fn sire_approval_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、9钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 9, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+        // 用户2允许用户1用kitty2
+        assert_ok!(KittiesModule::approve_siring(Origin::signed(2), 2, 1));
+        assert_eq!(SireApprovals::<Test>::get(2), Some(1));
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::SiringApproved(2, 2, 1).into()
+        );
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+        assert_eq!(KittiesModule::owner(4), Some(1));
+        assert_eq!(SireApprovals::<Test>::get(2), None);
+
+        // 用户2允许用户1用kitty3，然后把kitty3转给用户9
+        assert_ok!(KittiesModule::approve_siring(Origin::signed(2), 3, 1));
+        assert_ok!(KittiesModule::transfer(Origin::signed(2), 9, 3));
+        assert_eq!(SireApprovals::<Test>::get(3), None);
+    })
+}
+
+// 生过以后要过了冷却期才能再生
+#[test]
+fn breed_failed_when_parent_on_cooldown() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_couple();
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+        assert_eq!(NextBreedAt::<Test>::get(1), 15);
+        assert_eq!(NextBreedAt::<Test>::get(2), 15);
+        // kitty3转走，给用户1腾出位置
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 3));
+
+        System::set_block_number(14);
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 1, 2), Error::<Test>::KittyOnCooldown);
+
+        System::set_block_number(15);
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+    })
+}
+
+// 记录kitty的父母和代数
+#[test]
+fn breed_records_lineage() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_couple();
+        assert_eq!(KittyParents::<Test>::get(1), None);
+        assert_eq!(KittyGeneration::<Test>::get(1), 0);
+
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+        assert_eq!(KittyParents::<Test>::get(3), Some((1, 2)));
+        assert_eq!(KittyGeneration::<Test>::get(3), 1);
+
+        // kitty1转走，用kitty3（母）和kitty2生第2代
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        System::set_block_number(15);
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 3, 2));
+        assert_eq!(KittyParents::<Test>::get(4), Some((3, 2)));
+        assert_eq!(KittyGeneration::<Test>::get(4), 2);
+    })
+}
+
+// kitty锁定的钱跟着kitty走
+#[test]
+fn deposit_follows_kitty() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_couple();
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+        assert_eq!(BalancesModule::reserved_balance(2), 0);
+        assert_eq!(KittyDeposits::<Test>::get(1), 100);
+
+        // 生的kitty也要锁定
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+        assert_eq!(BalancesModule::reserved_balance(1), 300);
+        assert_eq!(KittyDeposits::<Test>::get(3), 100);
+
+        // 转给用户2，用户2锁定，用户1退回
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+        assert_eq!(BalancesModule::reserved_balance(2), 100);
+
+        // 用户2从市场上买kitty2，付10块钱，并且锁定
+        assert_ok!(KittiesModule::market(Origin::signed(1), 2, 10, None));
+        assert_ok!(KittiesModule::buy(Origin::signed(2), 2, 10, None));
+        assert_eq!(BalancesModule::reserved_balance(1), 100);
+        assert_eq!(BalancesModule::reserved_balance(2), 200);
+        assert_eq!(BalancesModule::free_balance(1), 910);
+        assert_eq!(BalancesModule::free_balance(2), 790);
+    })
+}
+
+// 新所有者的钱不够锁定时不能转移和购买
+#[test]
+fn transfer_and_buy_failed_when_cannot_cover_deposit() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱，用户2的钱不够锁定
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 50, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 1), Error::<Test>::CannotCoverDeposit);
+
+        // 用户2付得起价钱，但是付完以后不够锁定，买的钱也要退回来
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        assert_noop!(KittiesModule::buy(Origin::signed(2), 1, 10, None), Error::<Test>::CannotCoverDeposit);
+        assert_eq!(BalancesModule::free_balance(2), 50);
+        assert_eq!(KittiesModule::owner(1), Some(1));
+        assert_eq!(BalancesModule::reserved_balance(1), 100);
+    })
+}
+
+// 删除kitty，退回锁定的钱
+#[test]
+fn release_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        // 其它人不能删
+        assert_noop!(KittiesModule::release(Origin::signed(2), 1), Error::<Test>::NotOwner);
+
+        assert_ok!(KittiesModule::release(Origin::signed(1), 1));
+        assert_eq!(Kitties::<Test>::get(1), None);
+        assert_eq!(KittiesModule::owner(1), None);
+        assert_eq!(KittiesModule::kitties_market(1), None);
+        assert_eq!(KittyDeposits::<Test>::get(1), 0);
+        assert!(!OwnedKitties::<Test>::contains_key(1, 1));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 0);
+        assert_eq!(BalancesModule::reserved_balance(1), 0);
+        assert_eq!(BalancesModule::free_balance(1), 1_000);
+        // id不会重复使用
+        assert_eq!(KittiesCount::<Test>::get(), Some(1));
+
+        // 判断事件
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::KittyReleased(1, 1).into()
+        );
+
+        // 删掉的kitty不能再删
+        assert_noop!(KittiesModule::release(Origin::signed(1), 1), Error::<Test>::NotOwner);
+    })
+}
+
+// 拍卖kitty，出价最高的人在结束区块买走
+#[test]
+fn auction_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、3钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        // 保留价50，最小加价10，第20个区块结束
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 20));
+        assert_eq!(AuctionsEnding::<Test>::get(20).into_inner(), vec![1]);
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::AuctionCreated(1, 1, 50, 20).into()
+        );
+
+        // 第一次出价不能低于保留价
+        assert_noop!(KittiesModule::bid(Origin::signed(2), 1, 40), Error::<Test>::BidTooLow);
+        assert_ok!(KittiesModule::bid(Origin::signed(2), 1, 50));
+        assert_eq!(BalancesModule::reserved_balance(2), 50);
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::BidPlaced(2, 1, 50).into()
+        );
+
+        // 后面的出价至少多10，出价高了以后退回用户2的钱
+        assert_noop!(KittiesModule::bid(Origin::signed(3), 1, 55), Error::<Test>::BidTooLow);
+        assert_ok!(KittiesModule::bid(Origin::signed(3), 1, 60));
+        assert_eq!(BalancesModule::reserved_balance(2), 0);
+        assert_eq!(BalancesModule::reserved_balance(3), 60);
+
+        // 拍卖中的kitty不能转、挂单和删除
+        assert_noop!(KittiesModule::transfer(Origin::signed(1), 2, 1), Error::<Test>::KittyInAuction);
+        assert_noop!(KittiesModule::market(Origin::signed(1), 1, 10, None), Error::<Test>::KittyInAuction);
+        assert_noop!(KittiesModule::release(Origin::signed(1), 1), Error::<Test>::KittyInAuction);
+
+        // 第20个区块结束时成交
+        run_to_block(20);
+        assert_eq!(KittiesModule::owner(1), Some(1));
+        assert_noop!(KittiesModule::bid(Origin::signed(2), 1, 100), Error::<Test>::AuctionEnded);
+        run_to_block(21);
+        assert_eq!(KittiesModule::owner(1), Some(3));
+        assert_eq!(Auctions::<Test>::get(1), None);
+        assert_eq!(AuctionsEnding::<Test>::get(20).len(), 0);
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::AuctionSettled(1, 1, Some((3, 60))).into()
+        );
+
+        // 用户3付了60，锁定的钱跟着kitty走
+        assert_eq!(BalancesModule::free_balance(1), 1_060);
+        assert_eq!(BalancesModule::reserved_balance(1), 0);
+        assert_eq!(BalancesModule::free_balance(3), 840);
+        assert_eq!(BalancesModule::reserved_balance(3), 100);
+    })
+}
+
+// 快结束时有人出价，结束区块往后推
+#[test]
+fn auction_extended_by_late_bid() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 20));
+
+        // 第17个区块出价还早，不用推
+        run_to_block(17);
+        assert_ok!(KittiesModule::bid(Origin::signed(2), 1, 50));
+        assert_eq!(Auctions::<Test>::get(1).map(|a| a.end), Some(20));
+
+        // 第18个区块出价，推到第21个区块
+        run_to_block(18);
+        assert_ok!(KittiesModule::bid(Origin::signed(2), 1, 60));
+        assert_eq!(Auctions::<Test>::get(1).map(|a| a.end), Some(21));
+        assert_eq!(AuctionsEnding::<Test>::get(20).len(), 0);
+        assert_eq!(AuctionsEnding::<Test>::get(21).into_inner(), vec![1]);
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::AuctionExtended(1, 21).into()));
+
+        run_to_block(21);
+        assert_eq!(KittiesModule::owner(1), Some(1));
+        run_to_block(22);
+        assert_eq!(KittiesModule::owner(1), Some(2));
+    })
+}
+
+// 推后的结束区块已经满了时，放到之后第一个有空位的区块
+#[test]
+fn auction_extended_past_full_block() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 20));
+        // 第21个区块已经有2个拍卖结束
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 2, 50, 10, 21));
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 3, 50, 10, 21));
+
+        // 第18个区块出价，第21个区块满了，推到第22个区块
+        run_to_block(18);
+        assert_ok!(KittiesModule::bid(Origin::signed(2), 1, 50));
+        assert_eq!(Auctions::<Test>::get(1).map(|a| a.end), Some(22));
+        assert_eq!(AuctionsEnding::<Test>::get(20).len(), 0);
+        assert_eq!(AuctionsEnding::<Test>::get(21).into_inner(), vec![2, 3]);
+        assert_eq!(AuctionsEnding::<Test>::get(22).into_inner(), vec![1]);
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::AuctionExtended(1, 22).into()));
+
+        run_to_block(23);
+        assert_eq!(KittiesModule::owner(1), Some(2));
+    })
+}
+
+// 没有人出价，或者出价最高的人接不了kitty时，kitty留给卖家
+#[test]
+fn auction_settled_without_sale() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1钱包放点钱，用户2的钱付得起出价，但是不够锁定
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 150, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // kitty1没有人出价
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 20));
+        // kitty2用户2出价
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 2, 50, 10, 20));
+        assert_ok!(KittiesModule::bid(Origin::signed(2), 2, 60));
+
+        run_to_block(21);
+        assert_eq!(KittiesModule::owner(1), Some(1));
+        assert_eq!(KittiesModule::owner(2), Some(1));
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::AuctionSettled(1, 1, None).into()));
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::AuctionSettled(1, 2, None).into()));
+
+        // 出价的钱退回给用户2
+        assert_eq!(BalancesModule::free_balance(2), 150);
+        assert_eq!(BalancesModule::reserved_balance(2), 0);
+        assert_eq!(BalancesModule::free_balance(1), 800);
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+
+        // 拍卖结束以后可以再转
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+    })
+}
+
+// 创建拍卖和出价时的各种错误
+#[test]
+fn auction_failed_when_invalid() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // 其它人的kitty
+        assert_noop!(KittiesModule::create_auction(Origin::signed(2), 1, 50, 10, 20), Error::<Test>::NotOwner);
+        // 结束区块不在以后
+        assert_noop!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 10), Error::<Test>::InvalidAuctionEnd);
+        // 已经挂单了
+        assert_ok!(KittiesModule::market(Origin::signed(1), 3, 10, None));
+        assert_noop!(KittiesModule::create_auction(Origin::signed(1), 3, 50, 10, 20), Error::<Test>::KittyOnMarket);
+        assert_ok!(KittiesModule::unlist(Origin::signed(1), 3));
+
+        // 已经在拍卖了
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 20));
+        assert_noop!(KittiesModule::create_auction(Origin::signed(1), 1, 50, 10, 20), Error::<Test>::KittyInAuction);
+        // 一个区块最多结束2个拍卖
+        assert_ok!(KittiesModule::create_auction(Origin::signed(1), 2, 50, 10, 20));
+        assert_noop!(KittiesModule::create_auction(Origin::signed(1), 3, 50, 10, 20), Error::<Test>::AuctionQueueFull);
+
+        // 卖家不能出价，没有拍卖的kitty不能出价
+        assert_noop!(KittiesModule::bid(Origin::signed(1), 1, 50), Error::<Test>::BuyerIsOwner);
+        assert_noop!(KittiesModule::bid(Origin::signed(2), 3, 50), Error::<Test>::AuctionNotFound);
+    })
+}
+
+// 从dna解出属性
+#[test]
+fn dna_decode_works() {
+    assert_eq!(
+        dna::decode(&[215, 75, 66, 60, 234, 156, 146, 62, 247, 65, 230, 205, 192, 2, 31, 70]),
+        KittyAttributes {
+            gender: Gender::Female,
+            color: Color::Grey,
+            pattern: Pattern::Tabby,
+            rarity: Rarity::Common,
+        }
+    );
+    assert_eq!(
+        dna::decode(&[0u8; 16]),
+        KittyAttributes {
+            gender: Gender::Male,
+            color: Color::Black,
+            pattern: Pattern::Solid,
+            rarity: Rarity::Common,
+        }
+    );
+    assert_eq!(
+        dna::decode(&[255u8; 16]),
+        KittyAttributes {
+            gender: Gender::Female,
+            color: Color::Lilac,
+            pattern: Pattern::Calico,
+            rarity: Rarity::Legendary,
+        }
+    );
+
+    // 稀有度的边界
+    let rarity = |b: u8| dna::decode(&[0, 0, 0, b, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]).rarity;
+    assert_eq!(rarity(127), Rarity::Common);
+    assert_eq!(rarity(128), Rarity::Uncommon);
+    assert_eq!(rarity(192), Rarity::Rare);
+    assert_eq!(rarity(240), Rarity::Epic);
+    assert_eq!(rarity(253), Rarity::Epic);
+    assert_eq!(rarity(254), Rarity::Legendary);
+}
+
+// 查询kitty的属性
+#[test]
+fn kitty_attributes_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_eq!(KittiesModule::kitty_attributes(1), None);
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        // kitty1的dna是[13, 191, 43, 48, ...]
+        assert_eq!(
+            KittiesModule::kitty_attributes(1),
+            Some(KittyAttributes {
+                gender: Gender::Male,
+                color: Color::Brown,
+                pattern: Pattern::Solid,
+                rarity: Rarity::Common,
+            })
+        );
+    })
+}
+
+// 父母同一个性别不能生
+#[test]
+fn breed_failed_when_same_gender() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        // 用户1创建的两个kitty都是母的
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_noop!(KittiesModule::breed(Origin::signed(1), 1, 2), Error::<Test>::IncompatibleGender);
+    })
+}
+
+// 把kitty挂出来给别人配种，付配种费用它生孩子
+#[test]
+fn breed_with_sire_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、3钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        // 用户1的kitty1是母的，用户2的kitty2是公的
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+        // 用户2把kitty2挂出来，配种费30，第20个区块过期
+        assert_ok!(KittiesModule::list_sire(Origin::signed(2), 2, 30, 20));
+        assert_eq!(SireListings::<Test>::get(2), Some((30, 20)));
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::SireListed(2, 2, 30, 20).into()
+        );
+
+        // 出的钱比配种费低
+        assert_noop!(KittiesModule::breed_with_sire(Origin::signed(1), 1, 2, 20), Error::<Test>::PriceTooLow);
+
+        // 用户1用kitty1和kitty2生kitty3，孩子归用户1
+        assert_ok!(KittiesModule::breed_with_sire(Origin::signed(1), 1, 2, 30));
+        assert_eq!(KittiesModule::owner(3), Some(1));
+        assert_eq!(KittyParents::<Test>::get(3), Some((1, 2)));
+        assert_eq!(NextBreedAt::<Test>::get(2), 15);
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::SireUsed(1, 2, 3, 30).into()
+        );
+
+        // 配种费付给用户2，用户1还要为孩子锁定
+        assert_eq!(BalancesModule::free_balance(1), 770);
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+        assert_eq!(BalancesModule::free_balance(2), 930);
+
+        // 挂单还在，但是kitty2在冷却期
+        assert_eq!(SireListings::<Test>::get(2), Some((30, 20)));
+        assert_ok!(KittiesModule::create(Origin::signed(3)));
+        assert_noop!(KittiesModule::breed_with_sire(Origin::signed(3), 4, 2, 30), Error::<Test>::KittyOnCooldown);
+        System::set_block_number(15);
+        assert_ok!(KittiesModule::breed_with_sire(Origin::signed(3), 4, 2, 30));
+        assert_eq!(KittiesModule::owner(5), Some(3));
+    })
+}
+
+// 挂出来配种和用别人的kitty配种时的各种错误
+#[test]
+fn breed_with_sire_failed_when_invalid() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+
+        // 其它人的kitty，过期区块不在以后
+        assert_noop!(KittiesModule::list_sire(Origin::signed(1), 2, 30, 20), Error::<Test>::NotOwner);
+        assert_noop!(KittiesModule::list_sire(Origin::signed(2), 2, 30, 10), Error::<Test>::InvalidSireExpiry);
+
+        // 没有挂出来
+        assert_noop!(KittiesModule::breed_with_sire(Origin::signed(1), 1, 2, 30), Error::<Test>::SireNotListed);
+        assert_noop!(KittiesModule::unlist_sire(Origin::signed(2), 2), Error::<Test>::SireNotListed);
+
+        assert_ok!(KittiesModule::list_sire(Origin::signed(2), 2, 30, 20));
+        // 不能用自己挂出来的kitty
+        assert_noop!(KittiesModule::breed_with_sire(Origin::signed(2), 1, 2, 30), Error::<Test>::BuyerIsOwner);
+        // 自己这边的kitty不是自己的
+        assert_noop!(KittiesModule::breed_with_sire(Origin::signed(3), 1, 2, 30), Error::<Test>::NotOwner);
+        // 过期了
+        System::set_block_number(20);
+        assert_noop!(KittiesModule::breed_with_sire(Origin::signed(1), 1, 2, 30), Error::<Test>::SireListingExpired);
+
+        // 撤下来
+        assert_noop!(KittiesModule::unlist_sire(Origin::signed(1), 2), Error::<Test>::NotOwner);
+        assert_ok!(KittiesModule::unlist_sire(Origin::signed(2), 2));
+        assert_eq!(SireListings::<Test>::get(2), None);
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::SireUnlisted(2, 2).into()
+        );
+    })
+}
+
+// 转移kitty时，挂出来配种的单作废
+#[test]
+fn transfer_clears_sire_listing() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(2)));
+        assert_ok!(KittiesModule::list_sire(Origin::signed(2), 1, 30, 20));
+        assert_ok!(KittiesModule::transfer(Origin::signed(2), 1, 1));
+        assert_eq!(SireListings::<Test>::get(1), None);
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::SireUnlisted(2, 1).into()));
+    })
+}
+
+// 转卖kitty时分版税给创建者
+#[test]
+fn royalty_paid_on_resale() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、3钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_eq!(KittyCreators::<Test>::get(1), Some(1));
+
+        // 创建者自己卖，不分版税
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 100, None));
+        assert_ok!(KittiesModule::buy(Origin::signed(2), 1, 100, None));
+        assert_eq!(BalancesModule::free_balance(1), 1_100);
+        assert!(!System::events().iter().any(|r| matches!(
+            r.event,
+            mock::Event::KittiesModule(KittyEvent::RoyaltyPaid(..))
+        )));
+
+        // 用户2转卖，10%给创建者用户1
+        assert_ok!(KittiesModule::market(Origin::signed(2), 1, 200, None));
+        assert_ok!(KittiesModule::buy(Origin::signed(3), 1, 200, None));
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::RoyaltyPaid(1, 1, 20).into()));
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::KittyBuy(3, 1, 200, None).into()
+        );
+        assert_eq!(BalancesModule::free_balance(1), 1_120);
+        assert_eq!(BalancesModule::free_balance(2), 1_080);
+        assert_eq!(BalancesModule::free_balance(3), 700);
+        // 创建者不变
+        assert_eq!(KittyCreators::<Test>::get(1), Some(1));
+    })
+}
+
+// 拍卖成交时也分版税给创建者
+#[test]
+fn royalty_paid_on_auction() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        // 给用户1、2、3钱包放点钱
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+
+        assert_ok!(KittiesModule::create_auction(Origin::signed(2), 1, 50, 10, 20));
+        assert_ok!(KittiesModule::bid(Origin::signed(3), 1, 60));
+        run_to_block(21);
+        assert_eq!(KittiesModule::owner(1), Some(3));
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::RoyaltyPaid(1, 1, 6).into()));
+        assert_eq!(BalancesModule::free_balance(1), 1_006);
+        assert_eq!(BalancesModule::free_balance(2), 1_054);
+    })
+}
+
+// 建一个资产7，给用户3发500个
+fn create_asset() {
+    assert_ok!(Assets::force_create(Origin::root(), 7, 1, true, 1));
+    assert_ok!(Assets::mint(Origin::signed(1), 7, 3, 500));
+}
+
+// 用资产挂单，买的时候用资产付钱，版税也用资产付
+#[test]
+fn buy_with_asset_works() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        create_asset();
+        // 用户1创建，转给用户2卖
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+
+        assert_ok!(KittiesModule::market(Origin::signed(2), 1, 200, Some(7)));
+        assert_eq!(MarketAssets::<Test>::get(1), Some(7));
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::KittyMarket(2, 1, 200, Some(7)).into()
+        );
+
+        assert_ok!(KittiesModule::buy(Origin::signed(3), 1, 200, Some(7)));
+        assert_eq!(KittiesModule::owner(1), Some(3));
+        assert_eq!(MarketAssets::<Test>::get(1), None);
+        assert!(System::events().iter().any(|r| r.event == KittyEvent::RoyaltyPaid(1, 1, 20).into()));
+        assert_eq!(System::events().last().expect("events expected").event,
+                   KittyEvent::KittyBuy(3, 1, 200, Some(7)).into()
+        );
+
+        // 资产转了，原生币只有押金跟着kitty走
+        assert_eq!(Assets::balance(7, 3), 300);
+        assert_eq!(Assets::balance(7, 2), 180);
+        assert_eq!(Assets::balance(7, 1), 20);
+        assert_eq!(BalancesModule::free_balance(2), 1_000);
+        assert_eq!(BalancesModule::free_balance(3), 900);
+    })
+}
+
+// 资产不够时买不了，原生币再多也不行
+#[test]
+fn buy_with_asset_failed_when_have_no_asset() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_asset();
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 200, Some(7)));
+
+        assert_noop!(
+            KittiesModule::buy(Origin::signed(2), 1, 200, Some(7)),
+            pallet_assets::Error::<Test>::BalanceLow
+        );
+    })
+}
+
+// 买家给出的资产和挂单的不一样时买不了
+#[test]
+fn buy_failed_when_asset_mismatch() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 3, 1_000, 0));
+        create_asset();
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // 用资产挂单，买家按原生币买
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 200, Some(7)));
+        assert_noop!(
+            KittiesModule::buy(Origin::signed(3), 1, 200, None),
+            Error::<Test>::AssetMismatch
+        );
+
+        // 改成原生币挂单，买家还按资产买
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 200, None));
+        assert_noop!(
+            KittiesModule::buy(Origin::signed(3), 1, 200, Some(7)),
+            Error::<Test>::AssetMismatch
+        );
+    })
+}
+
+// 不存在的资产不能用来挂单
+#[test]
+fn market_failed_when_asset_unknown() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        assert_noop!(
+            KittiesModule::market(Origin::signed(1), 1, 10, Some(7)),
+            Error::<Test>::UnknownAsset
+        );
+    })
+}
+
+// 重新挂单、撤单和转移时，挂单用的资产跟着作废
+#[test]
+fn market_asset_cleared_with_listing() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(10);
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        create_asset();
+        assert_ok!(KittiesModule::create(Origin::signed(1)));
+
+        // 改成用原生币计价
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, Some(7)));
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, None));
+        assert_eq!(MarketAssets::<Test>::get(1), None);
+
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, Some(7)));
+        assert_ok!(KittiesModule::unlist(Origin::signed(1), 1));
+        assert_eq!(MarketAssets::<Test>::get(1), None);
+
+        assert_ok!(KittiesModule::market(Origin::signed(1), 1, 10, Some(7)));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        assert_eq!(KittiesModule::kitties_market(1), None);
+        assert_eq!(MarketAssets::<Test>::get(1), None);
+    })
+}
+
+// 创世区块里的kitty，锁定押金，可以挂单
+#[test]
+fn genesis_config_works() {
+    let t = mock::GenesisConfig {
+        system: Default::default(),
+        balances_module: BalancesModuleConfig { balances: vec![(1, 1_000), (2, 1_000)] },
+        kitties_module: KittiesModuleConfig {
+            kitties: vec![(1, [0u8; 16], None), (1, [255u8; 16], None), (2, [7u8; 16], Some(50))],
+        },
+    }
+    .build_storage()
+    .unwrap();
+
+    sp_io::TestExternalities::from(t).execute_with(|| {
+        assert_eq!(KittiesCount::<Test>::get(), Some(3));
+        assert_eq!(Kitties::<Test>::get(1).map(|kitty| kitty.dna), Some([0u8; 16]));
+        assert_eq!(KittiesModule::owner(2), Some(1));
+        assert_eq!(KittiesModule::owner(3), Some(2));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 2);
+        assert_eq!(KittyCreators::<Test>::get(3), Some(2));
+
+        // 押金锁定了
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+        assert_eq!(BalancesModule::reserved_balance(2), 100);
+        assert_eq!(KittyDeposits::<Test>::get(3), 100);
+
+        // kitty3挂单了，kitty1没有
+        assert_eq!(KittiesModule::kitties_market(1), None);
+        assert_eq!(KittiesModule::kitties_market(3), Some(50));
+
+        // 创世区块用的是新的存储，不用迁移
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+
+        // 用户1的一公一母可以生
+        System::set_block_number(10);
+        assert_ok!(KittiesModule::breed(Origin::signed(1), 1, 2));
+        assert_eq!(KittiesCount::<Test>::get(), Some(4));
+    })
+}
+
+// 按v1的布局写一个kitty的dna、所有者和价钱，是None的不写
+fn put_v1_kitty(kitty_id: u32, dna: Option<[u8; 16]>, owner: Option<u64>, price: Option<u64>) {
+    let key = Blake2_128Concat::hash(&kitty_id.encode());
+    if dna.is_some() {
+        put_storage_value(b"KittiesModule", b"Kitties", &key, dna);
+    }
+    if owner.is_some() {
+        put_storage_value(b"KittiesModule", b"Owner", &key, owner);
+    }
+    if price.is_some() {
+        put_storage_value(b"KittiesModule", b"KittiesMarket", &key, price);
+    }
+}
+
+fn v1_owner(kitty_id: u32) -> Option<Option<u64>> {
+    let key = Blake2_128Concat::hash(&kitty_id.encode());
+    get_storage_value(b"KittiesModule", b"Owner", &key)
+}
+
+fn v1_price(kitty_id: u32) -> Option<Option<u64>> {
+    let key = Blake2_128Concat::hash(&kitty_id.encode());
+    get_storage_value(b"KittiesModule", b"KittiesMarket", &key)
+}
+
+// v1的dna、所有者和价钱合成KittyInfo，并且只迁移一次
+#[test]
+fn migrate_v1_kitties_works() {
+    new_test_ext().execute_with(|| {
+        put_v1_kitty(1, Some([0u8; 16]), Some(1), Some(50));
+        put_v1_kitty(2, Some([255u8; 16]), Some(2), None);
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V1);
+
+        KittiesModule::on_runtime_upgrade();
+
+        assert_eq!(
+            Kitties::<Test>::get(1),
+            Some(KittyInfo { dna: [0u8; 16], owner: 1, price: Some(50) })
+        );
+        assert_eq!(
+            Kitties::<Test>::get(2),
+            Some(KittyInfo { dna: [255u8; 16], owner: 2, price: None })
+        );
+        assert_eq!(v1_owner(1), None);
+        assert_eq!(v1_price(1), None);
+        assert_eq!(v1_owner(2), None);
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+        assert_ok!(crate::migrations::post_migrate_to_v2::<Test>());
+
+        // 已经是V2时不会再迁移
+        put_v1_kitty(3, None, Some(3), None);
+        KittiesModule::on_runtime_upgrade();
+        assert_eq!(v1_owner(3), Some(Some(3)));
+    })
+}
+
+// 迁移时补上所有者索引和创建者，从所有者锁定押金，钱不够的不锁定
+#[test]
+fn migrate_v1_kitties_backfills_indexes() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        put_v1_kitty(1, Some([0u8; 16]), Some(1), Some(50));
+        put_v1_kitty(2, Some([255u8; 16]), Some(1), None);
+        put_v1_kitty(3, Some([255u8; 16]), Some(2), None);
+        KittiesCount::<Test>::put(3);
+
+        KittiesModule::on_runtime_upgrade();
+        assert_ok!(crate::migrations::post_migrate_to_v2::<Test>());
+
+        assert!(OwnedKitties::<Test>::contains_key(1, 1));
+        assert!(OwnedKitties::<Test>::contains_key(1, 2));
+        assert!(OwnedKitties::<Test>::contains_key(2, 3));
+        assert_eq!(OwnedKittiesCount::<Test>::get(1), 2);
+        assert_eq!(OwnedKittiesCount::<Test>::get(2), 1);
+        assert_eq!(KittyCreators::<Test>::get(1), Some(1));
+        assert_eq!(KittyCreators::<Test>::get(3), Some(2));
+        assert_eq!(KittyDeposits::<Test>::get(1), 100);
+        assert_eq!(KittyDeposits::<Test>::get(2), 100);
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+        assert_eq!(KittyDeposits::<Test>::get(3), 0);
+        assert_eq!(BalancesModule::reserved_balance(2), 0);
+
+        // 迁移过来的kitty和新建的一样可以转移，押金跟着走
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        assert_eq!(BalancesModule::reserved_balance(1), 100);
+        assert_eq!(BalancesModule::reserved_balance(2), 100);
+        assert_eq!(OwnedKittiesCount::<Test>::get(2), 2);
+    })
+}
+
+// 没有所有者的kitty和没有kitty的所有者、价钱在迁移时删掉
+#[test]
+fn migrate_v1_kitties_drops_incomplete_entries() {
+    new_test_ext().execute_with(|| {
+        put_v1_kitty(1, Some([0u8; 16]), None, Some(50));
+        put_v1_kitty(2, None, Some(2), Some(60));
+        put_v1_kitty(3, Some([255u8; 16]), Some(3), None);
+
+        KittiesModule::on_runtime_upgrade();
+
+        assert_eq!(Kitties::<Test>::get(1), None);
+        assert_eq!(Kitties::<Test>::get(2), None);
+        assert_eq!(KittiesModule::owner(3), Some(3));
+        assert_eq!(v1_price(1), None);
+        assert_eq!(v1_owner(2), None);
+        assert_eq!(v1_price(2), None);
+        assert_ok!(crate::migrations::post_migrate_to_v2::<Test>());
+    })
+}
+
+// v1创建kitty时锁定的钱还在所有者身上时直接记成押金，不再另外锁定
+#[test]
+fn migrate_v1_kitties_takes_over_legacy_reserve() {
+    new_test_ext().execute_with(|| {
+        // 用户1在v1里创建了两个kitty，锁定了两份钱；用户2的kitty是别人转给他的
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 1, 1_000, 0));
+        assert_ok!(BalancesModule::set_balance(Origin::root(), 2, 1_000, 0));
+        assert_ok!(BalancesModule::reserve(&1, 200));
+        put_v1_kitty(1, Some([0u8; 16]), Some(1), None);
+        put_v1_kitty(2, Some([255u8; 16]), Some(1), None);
+        put_v1_kitty(3, Some([255u8; 16]), Some(2), None);
+
+        KittiesModule::on_runtime_upgrade();
+        assert_ok!(crate::migrations::post_migrate_to_v2::<Test>());
+
+        assert_eq!(KittyDeposits::<Test>::get(1), 100);
+        assert_eq!(KittyDeposits::<Test>::get(2), 100);
+        assert_eq!(BalancesModule::reserved_balance(1), 200);
+        assert_eq!(KittyDeposits::<Test>::get(3), 100);
+        assert_eq!(BalancesModule::reserved_balance(2), 100);
+
+        // 转走kitty时押金也跟着走，原来锁定的钱不会留在用户1身上
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 1));
+        assert_ok!(KittiesModule::transfer(Origin::signed(1), 2, 2));
+        assert_eq!(BalancesModule::reserved_balance(1), 0);
+        assert_eq!(BalancesModule::free_balance(1), 1_000);
+    })
+}
+
+// 一个区块迁移不完的kitty在之后的区块里接着迁移
+#[test]
+fn migrate_v1_kitties_across_blocks() {
+    new_test_ext().execute_with(|| {
+        let count = crate::migrations::MIGRATION_BATCH as u32 + 1;
+        for kitty_id in 1..=count {
+            put_v1_kitty(kitty_id, Some([0u8; 16]), Some(kitty_id as u64), Some(50));
+        }
+
+        KittiesModule::on_runtime_upgrade();
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V1);
+        assert_eq!(KittyCreators::<Test>::iter().count(), crate::migrations::MIGRATION_BATCH);
+        assert_ok!(crate::migrations::post_migrate_to_v2::<Test>());
+
+        run_to_block(1);
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+        assert_ok!(crate::migrations::post_migrate_to_v2::<Test>());
+        for kitty_id in 1..=count {
+            assert_eq!(
+                Kitties::<Test>::get(kitty_id),
+                Some(KittyInfo { dna: [0u8; 16], owner: kitty_id as u64, price: Some(50) })
+            );
+            assert_eq!(v1_owner(kitty_id), None);
+        }
     })
 }
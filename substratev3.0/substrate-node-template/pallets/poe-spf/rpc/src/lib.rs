@@ -0,0 +1,216 @@
+//! RPC interface for the proof of existence pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_poe_spf::index::{self, ClaimFilter};
+pub use pallet_poe_spf_runtime_api::PoeApi as PoeRuntimeApi;
+use sc_rpc_api::DenyUnsafe;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{
+	offchain::{OffchainStorage, STORAGE_PREFIX},
+	Bytes, H256,
+};
+use sp_runtime::{
+	generic::BlockId,
+	traits::{AtLeast32BitUnsigned, Block as BlockT, Header as HeaderT},
+};
+
+/// PoE RPC methods.
+#[rpc]
+pub trait PoeApi<BlockHash, AccountId, BlockNumber> {
+	/// Check that `leaf` is included in the anchored Merkle `root`, according to `proof`.
+	#[rpc(name = "poe_verifyInclusion")]
+	fn verify_inclusion(
+		&self,
+		root: H256,
+		leaf: H256,
+		proof: Vec<H256>,
+		at: Option<BlockHash>,
+	) -> Result<bool>;
+
+	/// List a page of the claims owned by `owner`, with the block each claim was recorded at.
+	///
+	/// At most 100 claims are returned per page, whatever `limit` is.
+	#[rpc(name = "poe_claimsOf")]
+	fn claims_of(
+		&self,
+		owner: AccountId,
+		start: u32,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> Result<Vec<(Bytes, BlockNumber)>>;
+}
+
+/// PoE claim search over the offchain index.
+///
+/// The index is written by the pallet's offchain worker, so it is only kept when the node runs
+/// offchain workers (e.g. `--offchain-worker always`).
+#[rpc]
+pub trait PoeIndexApi<AccountId, BlockNumber> {
+	/// Search the indexed claims by owner, block range (inclusive) and metadata prefix.
+	///
+	/// Every filter is optional. Each matching claim is returned once, as
+	/// `(claim, owner, block, metadata)`, where `block` is the block the claim was created or
+	/// last transferred at. Candidates are looked up in the owner, metadata prefix or block
+	/// range index, in that order of preference, and only records of blocks on the canonical
+	/// chain are used. The matches are sorted by claim and paged by `start` and `limit`, with at
+	/// most 100 claims per page.
+	///
+	/// A search without an owner or metadata prefix reads every indexed claim of the block
+	/// range, so it is an unsafe RPC method.
+	#[rpc(name = "poe_searchClaims")]
+	fn search_claims(
+		&self,
+		owner: Option<AccountId>,
+		from: Option<BlockNumber>,
+		to: Option<BlockNumber>,
+		metadata_prefix: Option<Bytes>,
+		start: u32,
+		limit: u32,
+	) -> Result<Vec<(Bytes, AccountId, BlockNumber, Bytes)>>;
+}
+
+/// A struct that implements the [`PoeApi`].
+pub struct Poe<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Poe<C, B> {
+	/// Create new `Poe` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+/// Error code for failures while calling into the runtime.
+const RUNTIME_ERROR: i64 = 1;
+
+/// Maximum number of claims returned by one `poe_searchClaims` call.
+const MAX_SEARCH_PAGE: u32 = 100;
+
+fn runtime_error_into_rpc_err(err: impl std::fmt::Debug) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(RUNTIME_ERROR),
+		message: "Runtime error".into(),
+		data: Some(format!("{:?}", err).into()),
+	}
+}
+
+impl<C, Block, AccountId, BlockNumber> PoeApi<<Block as BlockT>::Hash, AccountId, BlockNumber>
+	for Poe<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static,
+	C: ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block>,
+	C::Api: PoeRuntimeApi<Block, AccountId, BlockNumber>,
+	AccountId: Codec,
+	BlockNumber: Codec,
+{
+	fn verify_inclusion(
+		&self,
+		root: H256,
+		leaf: H256,
+		proof: Vec<H256>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<bool> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+		let proof = proof.into_iter().map(|node| node.to_fixed_bytes()).collect();
+
+		api.verify_inclusion(&at, root.to_fixed_bytes(), leaf.to_fixed_bytes(), proof)
+			.map_err(runtime_error_into_rpc_err)
+	}
+
+	fn claims_of(
+		&self,
+		owner: AccountId,
+		start: u32,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> Result<Vec<(Bytes, BlockNumber)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		let claims =
+			api.claims_of(&at, owner, start, limit).map_err(runtime_error_into_rpc_err)?;
+
+		Ok(claims.into_iter().map(|(claim, block)| (claim.into(), block)).collect())
+	}
+}
+
+/// A struct that implements the [`PoeIndexApi`].
+pub struct PoeIndex<C, S, B> {
+	client: Arc<C>,
+	storage: S,
+	deny_unsafe: DenyUnsafe,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, S, B> PoeIndex<C, S, B> {
+	/// Create new `PoeIndex` reading the given offchain storage, checked against the client's
+	/// canonical chain.
+	pub fn new(client: Arc<C>, storage: S, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, storage, deny_unsafe, _marker: Default::default() }
+	}
+}
+
+impl<C, S, Block> PoeIndex<C, S, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block>,
+{
+	// Records are kept for every imported block, so only use the ones whose block is the
+	// canonical block at its height.
+	fn is_canonical(&self, hash: &<Block as BlockT>::Hash) -> bool {
+		match self.client.header(BlockId::hash(*hash)) {
+			Ok(Some(header)) => self.client.hash(*header.number()).ok().flatten() == Some(*hash),
+			_ => false,
+		}
+	}
+}
+
+impl<C, S, Block, AccountId, BlockNumber> PoeIndexApi<AccountId, BlockNumber>
+	for PoeIndex<C, S, Block>
+where
+	Block: BlockT,
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+	S: OffchainStorage + 'static,
+	AccountId: Codec + PartialEq + Send + Sync + 'static,
+	BlockNumber: Codec + AtLeast32BitUnsigned + Send + Sync + 'static,
+{
+	fn search_claims(
+		&self,
+		owner: Option<AccountId>,
+		from: Option<BlockNumber>,
+		to: Option<BlockNumber>,
+		metadata_prefix: Option<Bytes>,
+		start: u32,
+		limit: u32,
+	) -> Result<Vec<(Bytes, AccountId, BlockNumber, Bytes)>> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let filter =
+			ClaimFilter { owner, from, to, metadata_prefix: metadata_prefix.map(|p| p.to_vec()) };
+		let records = index::search(
+			|key| self.storage.get(STORAGE_PREFIX, key),
+			|hash| self.is_canonical(hash),
+			&filter,
+		);
+
+		Ok(records
+			.into_iter()
+			.filter_map(|record| {
+				let owner = record.owner?;
+				Some((record.claim.into(), owner, record.block_number, record.metadata.into()))
+			})
+			.skip(start as usize)
+			.take(limit.min(MAX_SEARCH_PAGE) as usize)
+			.collect())
+	}
+}
@@ -0,0 +1,22 @@
+//! Runtime API definition for the proof of existence pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait PoeApi<AccountId, BlockNumber> where
+		AccountId: Codec,
+		BlockNumber: Codec,
+	{
+		/// Whether `leaf` is included in the anchored Merkle `root`, according to `proof`.
+		fn verify_inclusion(root: [u8; 32], leaf: [u8; 32], proof: Vec<[u8; 32]>) -> bool;
+
+		/// A page of the claims owned by `owner`, with the block each claim was recorded at.
+		fn claims_of(owner: AccountId, start: u32, limit: u32) -> Vec<(Vec<u8>, BlockNumber)>;
+
+		/// The ownership changes of `claim` as `(previous owner, new owner, block)`, oldest first.
+		fn claim_history(claim: Vec<u8>) -> Vec<(AccountId, AccountId, BlockNumber)>;
+	}
+}
@@ -0,0 +1,281 @@
+//! Benchmarking setup for pallet-poe-spf
+
+use super::*;
+
+#[allow(unused)]
+use crate::Pallet as PoeSpf;
+use frame_benchmarking::{account, benchmarks, impl_benchmark_test_suite, whitelisted_caller};
+use frame_support::traits::{Currency, EnsureOrigin, Get, OnInitialize};
+use frame_system::RawOrigin;
+use sp_runtime::traits::Bounded;
+use sp_std::{vec, vec::Vec};
+
+const SEED: u32 = 0;
+
+// 生成长度为len的存证，前几个字节放index，保证批量操作里的存证各不相同
+fn claim_of(len: u32, index: u32) -> Vec<u8> {
+	let mut claim = vec![0u8; len as usize];
+	let prefix = claim.len().min(4);
+	claim[..prefix].copy_from_slice(&index.to_le_bytes()[..prefix]);
+	claim
+}
+
+// 给账户足够交押金的钱
+fn fund<T: Config>(who: &T::AccountId) {
+	T::Currency::make_free_balance_be(who, BalanceOf::<T>::max_value() / 2u32.into());
+}
+
+fn funded_account<T: Config>(name: &'static str, index: u32) -> T::AccountId {
+	let who = account(name, index, SEED);
+	fund::<T>(&who);
+	who
+}
+
+fn funded_caller<T: Config>() -> T::AccountId {
+	let caller = whitelisted_caller();
+	fund::<T>(&caller);
+	caller
+}
+
+// 过期区块，用来让存证进入清理队列
+fn expiry<T: Config>() -> Option<T::BlockNumber> {
+	Some(frame_system::Pallet::<T>::block_number() + 10u32.into())
+}
+
+// 创建一个会过期的存证，删除时要把它从清理队列里拿掉
+fn setup_claim<T: Config>(owner: &T::AccountId, claim: &[u8]) {
+	PoeSpf::<T>::create_claim(
+		RawOrigin::Signed(owner.clone()).into(),
+		claim.to_vec(),
+		expiry::<T>(),
+	)
+	.expect("claim can be created");
+}
+
+fn setup_offer<T: Config>(owner: &T::AccountId, claim: &[u8], receiver: &T::AccountId) {
+	PoeSpf::<T>::offer_claim(
+		RawOrigin::Signed(owner.clone()).into(),
+		claim.to_vec(),
+		receiver.clone(),
+		None,
+	)
+	.expect("claim can be offered");
+}
+
+benchmarks! {
+	create_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+	}: _(RawOrigin::Signed(caller.clone()), claim.clone(), expiry::<T>())
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|(owner, _)| owner), Some(caller));
+	}
+
+	revoke_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&caller, &claim);
+		setup_offer::<T>(&caller, &claim, &account("receiver", 0, SEED));
+	}: _(RawOrigin::Signed(caller), claim.clone())
+	verify {
+		assert!(!Proofs::<T>::contains_key(&claim));
+	}
+
+	transfer_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let caller = funded_caller::<T>();
+		let receiver = funded_account::<T>("receiver", 0);
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&caller, &claim);
+	}: _(RawOrigin::Signed(caller), claim.clone(), receiver.clone())
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|(owner, _)| owner), Some(receiver));
+	}
+
+	renew_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&caller, &claim);
+		let expires_at = frame_system::Pallet::<T>::block_number() + 20u32.into();
+	}: _(RawOrigin::Signed(caller), claim.clone(), Some(expires_at))
+	verify {
+		assert_eq!(ClaimExpiry::<T>::get(&claim), Some(expires_at));
+	}
+
+	create_merkle_claim {
+		let caller = funded_caller::<T>();
+		let root = [1u8; 32];
+	}: _(RawOrigin::Signed(caller), root, 1_000, expiry::<T>())
+	verify {
+		assert_eq!(MerkleRoots::<T>::get(root.to_vec()), Some(1_000));
+	}
+
+	offer_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let caller = funded_caller::<T>();
+		let receiver: T::AccountId = account("receiver", 0, SEED);
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&caller, &claim);
+	}: _(RawOrigin::Signed(caller), claim.clone(), receiver, expiry::<T>())
+	verify {
+		assert!(PendingOffers::<T>::contains_key(&claim));
+	}
+
+	accept_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let owner = funded_account::<T>("owner", 0);
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&owner, &claim);
+		setup_offer::<T>(&owner, &claim, &caller);
+	}: _(RawOrigin::Signed(caller.clone()), claim.clone())
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|(owner, _)| owner), Some(caller));
+	}
+
+	reject_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let owner = funded_account::<T>("owner", 0);
+		let caller: T::AccountId = whitelisted_caller();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&owner, &claim);
+		setup_offer::<T>(&owner, &claim, &caller);
+	}: _(RawOrigin::Signed(caller), claim.clone())
+	verify {
+		assert!(!PendingOffers::<T>::contains_key(&claim));
+	}
+
+	cancel_offer {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&caller, &claim);
+		setup_offer::<T>(&caller, &claim, &account("receiver", 0, SEED));
+	}: _(RawOrigin::Signed(caller), claim.clone())
+	verify {
+		assert!(!PendingOffers::<T>::contains_key(&claim));
+	}
+
+	create_shared_claim {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let o in 1 .. T::MaxCoOwners::get() - 1;
+		let caller = funded_caller::<T>();
+		let co_owners = (0 .. o).map(|i| account("co_owner", i, SEED)).collect::<Vec<_>>();
+		let claim = claim_of(l, 0);
+	}: _(RawOrigin::Signed(caller), claim.clone(), co_owners, o + 1, expiry::<T>())
+	verify {
+		assert_eq!(CoOwners::<T>::get(&claim).map(|(_, threshold)| threshold), Some(o + 1));
+	}
+
+	set_claim_metadata {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let m in 0 .. T::MaxMetadataLen::get();
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&caller, &claim);
+		let metadata = vec![b'm'; m as usize];
+	}: _(RawOrigin::Signed(caller), claim.clone(), metadata.clone())
+	verify {
+		assert_eq!(ClaimMetadata::<T>::get(&claim).into_inner(), metadata);
+	}
+
+	force_revoke {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let owner = funded_account::<T>("owner", 0);
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&owner, &claim);
+		let origin = T::ForceOrigin::successful_origin();
+	}: _<T::Origin>(origin, claim.clone(), 0)
+	verify {
+		assert!(!Proofs::<T>::contains_key(&claim));
+	}
+
+	force_transfer {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let owner = funded_account::<T>("owner", 0);
+		let receiver = funded_account::<T>("receiver", 0);
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&owner, &claim);
+		let origin = T::ForceOrigin::successful_origin();
+	}: _<T::Origin>(origin, claim.clone(), receiver.clone(), 0)
+	verify {
+		assert_eq!(Proofs::<T>::get(&claim).map(|(owner, _)| owner), Some(receiver));
+	}
+
+	open_dispute {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let owner = funded_account::<T>("owner", 0);
+		let caller = funded_caller::<T>();
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&owner, &claim);
+	}: _(RawOrigin::Signed(caller), claim.clone(), 0)
+	verify {
+		assert!(Disputes::<T>::contains_key(&claim));
+	}
+
+	// 争议成立时要删除存证，比不成立更重
+	resolve_dispute {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let owner = funded_account::<T>("owner", 0);
+		let opener = funded_account::<T>("opener", 0);
+		let claim = claim_of(l, 0);
+		setup_claim::<T>(&owner, &claim);
+		PoeSpf::<T>::open_dispute(RawOrigin::Signed(opener).into(), claim.clone(), 0)?;
+		let origin = T::ForceOrigin::successful_origin();
+	}: _<T::Origin>(origin, claim.clone(), true)
+	verify {
+		assert!(!Proofs::<T>::contains_key(&claim));
+	}
+
+	create_claims_batch {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let b in 1 .. T::MaxBatchSize::get();
+		let caller = funded_caller::<T>();
+		let claims = (0 .. b).map(|i| claim_of(l, i)).collect::<Vec<_>>();
+	}: _(RawOrigin::Signed(caller.clone()), claims, None, BatchMode::AllOrNothing)
+	verify {
+		assert_eq!(OwnerClaims::<T>::iter_prefix(&caller).count(), b as usize);
+	}
+
+	revoke_claims_batch {
+		let l in 1 .. T::ClaimLimit::get() as u32;
+		let b in 1 .. T::MaxBatchSize::get();
+		let caller = funded_caller::<T>();
+		let claims = (0 .. b).map(|i| claim_of(l, i)).collect::<Vec<_>>();
+		PoeSpf::<T>::create_claims_batch(
+			RawOrigin::Signed(caller.clone()).into(),
+			claims.clone(),
+			None,
+			BatchMode::AllOrNothing,
+		)?;
+	}: _(RawOrigin::Signed(caller.clone()), claims, BatchMode::AllOrNothing)
+	verify {
+		assert_eq!(OwnerClaims::<T>::iter_prefix(&caller).count(), 0);
+	}
+
+	// 在同一个区块过期的n个存证，都带描述信息，删除时要一起清理
+	expire_claim {
+		let n in 0 .. T::MaxExpiringPerBlock::get();
+		let caller = funded_caller::<T>();
+		let at = expiry::<T>().expect("expiry is always set");
+		let claims = (0 .. n).map(|i| claim_of(T::ClaimLimit::get() as u32, i)).collect::<Vec<_>>();
+		for claim in &claims {
+			setup_claim::<T>(&caller, claim);
+			PoeSpf::<T>::set_claim_metadata(
+				RawOrigin::Signed(caller.clone()).into(),
+				claim.clone(),
+				vec![b'm'; T::MaxMetadataLen::get() as usize],
+			)?;
+		}
+	}: {
+		PoeSpf::<T>::on_initialize(at);
+	}
+	verify {
+		assert_eq!(OwnerClaims::<T>::iter_prefix(&caller).count(), 0);
+	}
+}
+
+impl_benchmark_test_suite!(PoeSpf, crate::mock::new_test_ext(), crate::mock::Test);
@@ -0,0 +1,221 @@
+//! 存证的链下索引：链下工作机把每个区块里有变化的存证写进链下数据库，节点的 RPC 在这里查询。
+//!
+//! 区块执行时只记下有变化的存证，区块导入后链下工作机在这个区块的状态上读出存证现在的所有者、
+//! 区块和描述信息，连同这个区块的区块号和哈希追加到存证的历史记录里。兄弟区块的哈希不同，
+//! 各自的记录都会保留；查询时只用哈希在规范链上的记录，同一个存证以区块号最大的记录为准，
+//! 所有者为 None 的记录表示存证已经删除。
+//!
+//! 另外按所有者、区块分桶和描述信息前缀各维护一份存证列表，查询时先用其中一个列表找出候选存证，
+//! 再读候选存证的历史记录，不用遍历整条链。这些列表只追加不删除，存证转移或者删除后可能还留在里面，
+//! 所以候选存证要用它在规范链上的最新记录重新过滤。
+//!
+//! 节点要运行链下工作机（比如 `--offchain-worker always`）才会写这些记录，
+//! 没有运行链下工作机的区块里的变化不会被索引到。
+
+use codec::{Codec, Decode, Encode};
+use sp_runtime::{
+	offchain::{
+		storage::StorageValueRef,
+		storage_lock::{StorageLock, Time},
+	},
+	traits::AtLeast32BitUnsigned,
+	RuntimeDebug,
+};
+use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
+
+/// 存证历史记录的键前缀
+pub const CLAIM_PREFIX: &[u8] = b"poe_spf::claim::";
+
+/// 按所有者的存证列表的键前缀
+pub const OWNER_PREFIX: &[u8] = b"poe_spf::owner::";
+
+/// 按区块分桶的存证列表的键前缀
+pub const BUCKET_PREFIX: &[u8] = b"poe_spf::bucket::";
+
+/// 按描述信息前缀的存证列表的键前缀
+pub const METADATA_PREFIX: &[u8] = b"poe_spf::metadata::";
+
+/// 保存已经写过的最大分桶号的键
+pub const MAX_BUCKET_KEY: &[u8] = b"poe_spf::max_bucket";
+
+// 多个区块的链下工作机可能同时运行，写索引前先拿到这个锁
+const LOCK_KEY: &[u8] = b"poe_spf::lock";
+
+/// 每个分桶包含的区块数
+pub const BUCKET_SIZE: u32 = 1_000;
+
+/// 描述信息最多按前几个字节建列表，更长的前缀先用这个长度的列表找候选存证再过滤
+pub const METADATA_KEY_LEN: usize = 4;
+
+fn key_of<K: Encode + ?Sized>(prefix: &[u8], k: &K) -> Vec<u8> {
+	let mut key = prefix.to_vec();
+	k.encode_to(&mut key);
+	key
+}
+
+/// 存证历史记录的键
+pub fn claim_key(claim: &[u8]) -> Vec<u8> {
+	key_of(CLAIM_PREFIX, claim)
+}
+
+/// 所有者的存证列表的键
+pub fn owner_key<AccountId: Encode>(owner: &AccountId) -> Vec<u8> {
+	key_of(OWNER_PREFIX, owner)
+}
+
+/// 第 bucket 个分桶的存证列表的键
+pub fn bucket_key(bucket: u32) -> Vec<u8> {
+	key_of(BUCKET_PREFIX, &bucket)
+}
+
+/// 描述信息以 prefix 开头的存证列表的键，prefix 最多取 [`METADATA_KEY_LEN`] 个字节
+pub fn metadata_key(prefix: &[u8]) -> Vec<u8> {
+	key_of(METADATA_PREFIX, &prefix[..prefix.len().min(METADATA_KEY_LEN)])
+}
+
+/// 区块所在的分桶
+pub fn bucket_of<BlockNumber: AtLeast32BitUnsigned>(block_number: BlockNumber) -> u32 {
+	let block_number: u32 = block_number.unique_saturated_into();
+	block_number / BUCKET_SIZE
+}
+
+/// 一条存证记录
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ClaimRecord<AccountId, BlockNumber> {
+	pub claim: Vec<u8>,
+	// None 表示存证已经删除
+	pub owner: Option<AccountId>,
+	// 存证最后一次创建或转移的区块
+	pub block_number: BlockNumber,
+	pub metadata: Vec<u8>,
+}
+
+/// 存证历史里的一项：写这条记录的区块号、区块哈希和记录
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct IndexEntry<AccountId, BlockNumber, Hash> {
+	pub at: BlockNumber,
+	pub at_hash: Hash,
+	pub record: ClaimRecord<AccountId, BlockNumber>,
+}
+
+/// 查询条件，没有设置的条件不过滤
+#[derive(Clone, PartialEq, Eq, RuntimeDebug)]
+pub struct ClaimFilter<AccountId, BlockNumber> {
+	pub owner: Option<AccountId>,
+	// 区块范围，两端都包含
+	pub from: Option<BlockNumber>,
+	pub to: Option<BlockNumber>,
+	pub metadata_prefix: Option<Vec<u8>>,
+}
+
+impl<AccountId: PartialEq, BlockNumber: PartialOrd> ClaimFilter<AccountId, BlockNumber> {
+	/// 记录是否满足查询条件，已删除的存证不满足
+	pub fn matches(&self, record: &ClaimRecord<AccountId, BlockNumber>) -> bool {
+		let owner = match &record.owner {
+			Some(owner) => owner,
+			None => return false,
+		};
+
+		self.owner.as_ref().map_or(true, |o| o == owner) &&
+			self.from.as_ref().map_or(true, |from| &record.block_number >= from) &&
+			self.to.as_ref().map_or(true, |to| &record.block_number <= to) &&
+			self.metadata_prefix.as_ref().map_or(true, |p| record.metadata.starts_with(p))
+	}
+}
+
+// 往链下数据库里的列表追加一项，已经有的不重复追加
+fn append<V: Codec + PartialEq>(key: &[u8], item: V) {
+	let _ = StorageValueRef::persistent(key).mutate(|items: Result<Option<Vec<V>>, _>| {
+		let mut items = items.ok().flatten().unwrap_or_default();
+		if !items.contains(&item) {
+			items.push(item);
+		}
+		Ok::<_, ()>(items)
+	});
+}
+
+/// 把区块 at（哈希是 at_hash）里存证的记录写进索引，只能在链下工作机里调用
+pub fn write<AccountId, BlockNumber, Hash>(
+	at: BlockNumber,
+	at_hash: Hash,
+	records: Vec<ClaimRecord<AccountId, BlockNumber>>,
+) where
+	AccountId: Codec + PartialEq,
+	BlockNumber: Codec + AtLeast32BitUnsigned,
+	Hash: Codec + Clone + PartialEq,
+{
+	let mut lock = StorageLock::<Time>::new(LOCK_KEY);
+	let _guard = lock.lock();
+
+	for record in records {
+		let claim = record.claim.clone();
+
+		// 已删除的存证只追加历史记录
+		if let Some(owner) = &record.owner {
+			append(&owner_key(owner), claim.clone());
+
+			let bucket = bucket_of(record.block_number.clone());
+			append(&bucket_key(bucket), claim.clone());
+			let _ = StorageValueRef::persistent(MAX_BUCKET_KEY).mutate(
+				|max: Result<Option<u32>, _>| {
+					Ok::<_, ()>(max.ok().flatten().map_or(bucket, |max| max.max(bucket)))
+				},
+			);
+
+			let len = record.metadata.len().min(METADATA_KEY_LEN);
+			for l in 1..=len {
+				append(&metadata_key(&record.metadata[..l]), claim.clone());
+			}
+		}
+
+		let entry = IndexEntry { at: at.clone(), at_hash: at_hash.clone(), record };
+		append(&claim_key(&claim), entry);
+	}
+}
+
+fn decode_list<V: Decode>(value: Option<Vec<u8>>) -> Vec<V> {
+	value.and_then(|v| Vec::<V>::decode(&mut &v[..]).ok()).unwrap_or_default()
+}
+
+/// 用 get 读链下数据库，is_canonical 判断区块哈希是否在规范链上，
+/// 返回满足条件的存证在规范链上的最新记录，按存证排序
+pub fn search<AccountId, BlockNumber, Hash>(
+	get: impl Fn(&[u8]) -> Option<Vec<u8>>,
+	is_canonical: impl Fn(&Hash) -> bool,
+	filter: &ClaimFilter<AccountId, BlockNumber>,
+) -> Vec<ClaimRecord<AccountId, BlockNumber>>
+where
+	AccountId: Decode + Encode + PartialEq,
+	BlockNumber: Decode + AtLeast32BitUnsigned,
+	Hash: Decode,
+{
+	// 按最能缩小范围的条件找候选存证：所有者、描述信息前缀，最后是区块范围内的分桶
+	let candidates: BTreeSet<Vec<u8>> = match (&filter.owner, &filter.metadata_prefix) {
+		(Some(owner), _) => decode_list(get(&owner_key(owner))).into_iter().collect(),
+		(None, Some(prefix)) if !prefix.is_empty() =>
+			decode_list(get(&metadata_key(prefix))).into_iter().collect(),
+		_ => {
+			let max = match get(MAX_BUCKET_KEY).and_then(|v| u32::decode(&mut &v[..]).ok()) {
+				Some(max) => max,
+				None => return Vec::new(),
+			};
+			let first = filter.from.clone().map_or(0, bucket_of);
+			let last = filter.to.clone().map_or(max, |to| bucket_of(to).min(max));
+			(first..=last)
+				.flat_map(|bucket| decode_list::<Vec<u8>>(get(&bucket_key(bucket))))
+				.collect()
+		},
+	};
+
+	candidates
+		.into_iter()
+		.filter_map(|claim| {
+			decode_list::<IndexEntry<AccountId, BlockNumber, Hash>>(get(&claim_key(&claim)))
+				.into_iter()
+				.filter(|entry| is_canonical(&entry.at_hash))
+				.max_by(|a, b| a.at.cmp(&b.at))
+				.map(|entry| entry.record)
+		})
+		.filter(|record| filter.matches(record))
+		.collect()
+}
@@ -2,17 +2,74 @@
 
 pub use pallet::*;
 
+pub mod index;
+pub mod merkle;
+pub mod migrations;
+pub mod weights;
+
+pub use weights::WeightInfo;
+
 #[cfg(test)]
 mod mock;
 
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
 #[frame_support::pallet]
 pub mod pallet {
-	use frame_support::{dispatch::DispatchResultWithPostInfo, pallet_prelude::*};
+	use frame_support::{
+		dispatch::{DispatchResult, DispatchResultWithPostInfo},
+		pallet_prelude::*,
+		traits::{BalanceStatus, Currency, EnsureOrigin, ReservableCurrency},
+		transactional,
+	};
+	use codec::{Decode, Encode};
 	use frame_system::pallet_prelude::*;
-	use sp_std::vec::Vec;
+	use sp_runtime::traits::Saturating;
+	use crate::{index, merkle, weights::WeightInfo};
+	use sp_std::{convert::TryFrom, vec::Vec};
+
+	// 分页查询一次最多返回的存证数
+	pub const MAX_CLAIMS_PER_PAGE: u32 = 100;
+
+	pub type BalanceOf<T> =
+		<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+	// 批量操作的模式
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum BatchMode {
+		// 有一个失败就全部回滚
+		AllOrNothing,
+		// 跳过失败的，保留成功的
+		BestEffort,
+	}
+
+	// 存储的版本，用来判断需不需要迁移
+	#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+	pub enum Releases {
+		// v2 节点的存储，存证在 TemplateModule 前缀下
+		V2,
+		// 当前的存储
+		V3,
+	}
+
+	impl Default for Releases {
+		fn default() -> Self {
+			Releases::V2
+		}
+	}
+
+	// 共有存证需要共有人同意的操作
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug)]
+	pub enum ClaimAction<AccountId> {
+		// 删除存证
+		Revoke,
+		// 把存证转移给这个账户
+		Transfer(AccountId),
+	}
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config {
@@ -20,17 +77,180 @@ pub mod pallet {
 
 		// 证明长度的声明
 		type ClaimLimit: Get<usize>;
+
+		// 押金使用的货币
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		// 每个存证的基础押金
+		#[pallet::constant]
+		type ClaimDepositBase: Get<BalanceOf<Self>>;
+
+		// 存证每个字节的押金
+		#[pallet::constant]
+		type ClaimDepositPerByte: Get<BalanceOf<Self>>;
+
+		// 每个区块最多能有多少个存证过期
+		#[pallet::constant]
+		type MaxExpiringPerBlock: Get<u32>;
+
+		// 批量操作最多能处理多少个存证
+		#[pallet::constant]
+		type MaxBatchSize: Get<u32>;
+
+		// 每个存证最多保留多少条所有者变更记录
+		#[pallet::constant]
+		type MaxHistoryLen: Get<u32>;
+
+		// 可以强制删除、转移存证和处理争议的来源，比如sudo或者议会
+		type ForceOrigin: EnsureOrigin<Self::Origin>;
+
+		// 发起争议需要锁定的押金
+		#[pallet::constant]
+		type DisputeDeposit: Get<BalanceOf<Self>>;
+
+		// 一个共有存证最多有多少个共有人
+		#[pallet::constant]
+		type MaxCoOwners: Get<u32>;
+
+		// 共有存证每个待执行操作的押金，由第一个同意的共有人锁定
+		#[pallet::constant]
+		type ApprovalDeposit: Get<BalanceOf<Self>>;
+
+		// 存证描述信息的最大长度
+		#[pallet::constant]
+		type MaxMetadataLen: Get<u32>;
+
+		// 各个方法的权重
+		type WeightInfo: WeightInfo;
 	}
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
 	pub struct Pallet<T>(_);
 
+	// 存储的版本，没有迁移过的节点是V2，新链创世时就是V3
+	#[pallet::storage]
+	#[pallet::getter(fn storage_version)]
+	pub type StorageVersion<T: Config> = StorageValue<_, Releases, ValueQuery>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn proofs)]
 	pub type Proofs<T: Config> =
 		StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, T::BlockNumber)>;
 
+	// 按所有者索引的存证，用来列出一个账户拥有的所有存证
+	#[pallet::storage]
+	#[pallet::getter(fn owner_claims)]
+	pub type OwnerClaims<T: Config> =
+		StorageDoubleMap<_, Blake2_128Concat, T::AccountId, Blake2_128Concat, Vec<u8>, ()>;
+
+	// 存证当前所有者锁定的押金
+	#[pallet::storage]
+	#[pallet::getter(fn claim_deposits)]
+	pub type ClaimDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, BalanceOf<T>, ValueQuery>;
+
+	// 存证的过期区块，没有就是永久有效
+	#[pallet::storage]
+	#[pallet::getter(fn claim_expiry)]
+	pub type ClaimExpiry<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, T::BlockNumber>;
+
+	// 每个区块要清理的过期存证队列
+	#[pallet::storage]
+	#[pallet::getter(fn expiry_queue)]
+	pub type ExpiryQueue<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		BoundedVec<Vec<u8>, T::MaxExpiringPerBlock>,
+		ValueQuery,
+	>;
+
+	// 作为默克尔根的存证，值是叶子的数量
+	#[pallet::storage]
+	#[pallet::getter(fn merkle_roots)]
+	pub type MerkleRoots<T: Config> = StorageMap<_, Blake2_128Concat, Vec<u8>, u32>;
+
+	// 存证的所有者变更记录：(原所有者, 新所有者, 变更的区块)，最早的记录在前面
+	#[pallet::storage]
+	#[pallet::getter(fn claim_history)]
+	pub type ClaimHistory<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		BoundedVec<(T::AccountId, T::AccountId, T::BlockNumber), T::MaxHistoryLen>,
+		ValueQuery,
+	>;
+
+	// 有争议的存证：(发起人, 原因代码, 发起的区块)，处理之前存证被冻结
+	#[pallet::storage]
+	#[pallet::getter(fn disputes)]
+	pub type Disputes<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, u8, T::BlockNumber)>;
+
+	// 共有存证的共有人和执行操作需要的同意数，Proofs里的所有者是创建者，押金也由创建者锁定
+	#[pallet::storage]
+	#[pallet::getter(fn co_owners)]
+	pub type CoOwners<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		(BoundedVec<T::AccountId, T::MaxCoOwners>, u32),
+	>;
+
+	// 共有存证每个待执行操作已经同意的共有人，操作执行以后清空
+	#[pallet::storage]
+	#[pallet::getter(fn pending_approvals)]
+	pub type PendingApprovals<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		ClaimAction<T::AccountId>,
+		BoundedVec<T::AccountId, T::MaxCoOwners>,
+		ValueQuery,
+	>;
+
+	// 共有存证每个待执行操作锁定的押金：(锁定押金的共有人, 押金)，同意记录清空时返还
+	#[pallet::storage]
+	#[pallet::getter(fn approval_deposits)]
+	pub type ApprovalDeposits<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		Blake2_128Concat,
+		ClaimAction<T::AccountId>,
+		(T::AccountId, BalanceOf<T>),
+	>;
+
+	// 存证的描述信息，会写进链下索引，用来按前缀查询
+	#[pallet::storage]
+	#[pallet::getter(fn claim_metadata)]
+	pub type ClaimMetadata<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, BoundedVec<u8, T::MaxMetadataLen>, ValueQuery>;
+
+	// 描述信息锁定的押金：(设置描述信息的人, 押金)，描述信息被覆盖、清空或者存证删除时返还
+	#[pallet::storage]
+	#[pallet::getter(fn metadata_deposits)]
+	pub type MetadataDeposits<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, (T::AccountId, BalanceOf<T>)>;
+
+	// 区块里有变化、要由链下工作机写进链下索引的存证，下一个区块开始时删除
+	#[pallet::storage]
+	#[pallet::getter(fn indexed_claims)]
+	pub type IndexedClaims<T: Config> =
+		StorageMap<_, Twox64Concat, T::BlockNumber, Vec<Vec<u8>>, ValueQuery>;
+
+	// 等待接收者同意的存证转移：(接收者, 转移请求的过期区块)
+	#[pallet::storage]
+	#[pallet::getter(fn pending_offers)]
+	pub type PendingOffers<T: Config> = StorageMap<
+		_,
+		Blake2_128Concat,
+		Vec<u8>,
+		(T::AccountId, Option<T::BlockNumber>),
+	>;
+
 	#[pallet::event]
 	#[pallet::metadata(T::AccountId = "AccountId")]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
@@ -38,6 +258,22 @@ pub mod pallet {
 		ClaimCreated(T::AccountId, Vec<u8>),
 		ClaimRevoked(T::AccountId, Vec<u8>),
 		ClaimTransfer(T::AccountId, Vec<u8>, T::AccountId),
+		ClaimRenewed(T::AccountId, Vec<u8>, Option<T::BlockNumber>),
+		ClaimExpired(T::AccountId, Vec<u8>),
+		ClaimCreateFailed(T::AccountId, Vec<u8>, DispatchError),
+		ClaimRevokeFailed(T::AccountId, Vec<u8>, DispatchError),
+		MerkleRootAnchored(T::AccountId, Vec<u8>, u32),
+		ClaimOffered(T::AccountId, Vec<u8>, T::AccountId, Option<T::BlockNumber>),
+		ClaimOfferAccepted(T::AccountId, Vec<u8>, T::AccountId),
+		ClaimOfferRejected(T::AccountId, Vec<u8>),
+		ClaimOfferCancelled(T::AccountId, Vec<u8>),
+		ClaimForceRevoked(T::AccountId, Vec<u8>, u8),
+		ClaimForceTransferred(T::AccountId, Vec<u8>, T::AccountId, u8),
+		DisputeOpened(T::AccountId, Vec<u8>, u8),
+		DisputeResolved(Vec<u8>, bool),
+		SharedClaimCreated(T::AccountId, Vec<u8>, Vec<T::AccountId>, u32),
+		ApprovalRecorded(T::AccountId, Vec<u8>, u32),
+		ClaimMetadataSet(T::AccountId, Vec<u8>),
 	}
 
 	#[pallet::error]
@@ -47,47 +283,105 @@ pub mod pallet {
 		ClaimLimitOver,
 		NotClaimOwner,
 		OwnerEqualReceiver,
+		InvalidExpiry,
+		ExpiryQueueFull,
+		BatchTooLarge,
+		OfferNotExist,
+		NotOfferReceiver,
+		OfferExpired,
+		ClaimFrozen,
+		DisputeAlreadyOpen,
+		DisputeNotExist,
+		TooManyCoOwners,
+		DuplicateCoOwner,
+		InvalidThreshold,
+		AlreadyApproved,
+		ClaimIsShared,
+		MetadataTooLong,
+	}
+
+	// 新链不需要迁移，创世时直接写上最新的存储版本
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			StorageVersion::<T>::put(Releases::V3);
+		}
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		// 每个区块开始时清理在这个区块过期的存证
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			// 上一个区块的链下工作机已经在它自己的状态上读过了
+			IndexedClaims::<T>::remove(now.saturating_sub(1u32.into()));
+
+			let expiring = ExpiryQueue::<T>::take(now).into_inner();
+			let count = expiring.len() as u32;
+
+			for claim in expiring {
+				Self::expire_claim(claim, now);
+			}
+
+			// 升级时没搬完的v2存证接着搬，另外算上删除上一个区块有变化的存证
+			T::WeightInfo::expire_claim(count)
+				.saturating_add(T::DbWeight::get().writes(1))
+				.saturating_add(crate::migrations::migrate_to_v3::<T>())
+		}
+
+		// 把这个区块里有变化的存证写进链下索引
+		fn offchain_worker(n: T::BlockNumber) {
+			Self::write_index(n);
+		}
+
+		// 把v2节点的存证搬到v3的存储里，一次搬不完的留给之后的区块
+		fn on_runtime_upgrade() -> Weight {
+			crate::migrations::migrate_to_v3::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<(), &'static str> {
+			crate::migrations::pre_migrate_to_v3::<T>()
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade() -> Result<(), &'static str> {
+			crate::migrations::post_migrate_to_v3::<T>()
+		}
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		// 创建存证的方法
-		#[pallet::weight(0)]
-		pub fn create_claim(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
-			ensure!(claim.len() <= T::ClaimLimit::get(), Error::<T>::ClaimLimitOver);
-
+		#[pallet::weight(T::WeightInfo::create_claim(claim.len() as u32))]
+		pub fn create_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
 
-			ensure!(!Proofs::<T>::contains_key(&claim), Error::<T>::ProofAlreadyExist);
-
-			Proofs::<T>::insert(
-				&claim,
-				(sender.clone(), frame_system::Pallet::<T>::block_number()),
-			);
-
-			Self::deposit_event(Event::ClaimCreated(sender, claim));
+			Self::do_create_claim(&sender, claim, expires_at)?;
 
 			Ok(().into())
 		}
 
 		// 删除存证的方法
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::revoke_claim(claim.len() as u32))]
 		pub fn revoke_claim(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
 			let sender = ensure_signed(origin)?;
 
-			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
-
-			ensure!(owner == sender, Error::<T>::NotClaimOwner);
-
-			Proofs::<T>::remove(&claim);
-
-			Self::deposit_event(Event::ClaimRevoked(sender, claim));
+			Self::do_revoke_claim(&sender, claim)?;
 
 			Ok(().into())
 		}
 
 		// 转移存证的方法
-		#[pallet::weight(0)]
+		#[pallet::weight(T::WeightInfo::transfer_claim(claim.len() as u32))]
+		#[transactional]
 		pub fn transfer_claim(
 			origin: OriginFor<T>,
 			claim: Vec<u8>,
@@ -98,21 +392,753 @@ pub mod pallet {
 			let (owner, _block_number) =
 				Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
 
-			ensure!((owner == sender), Error::<T>::NotClaimOwner);
+			Self::ensure_owner(&claim, &owner, &sender)?;
+
+			Self::ensure_not_frozen(&claim)?;
 
 			// 如果转给自己，就返回一个错误：所有者和接收者是相同的
 			ensure!((owner != receiver), Error::<T>::OwnerEqualReceiver);
 
-			// 保存新的存证，存证所有者为接收者参数的值，会直接覆盖旧值，map修改值的方法有2:insert and mutate
-			Proofs::<T>::insert(
+			// 共有存证同意数不够时只记录同意，先不转移
+			if !Self::approve(&claim, &sender, ClaimAction::Transfer(receiver.clone()))? {
+				return Ok(().into())
+			}
+
+			Self::do_transfer_claim(&claim, &owner, &receiver)?;
+
+			// 事件，表示owner把claim转移给receiver
+			Self::deposit_event(Event::ClaimTransfer(owner, claim, receiver));
+
+			Ok(().into())
+		}
+
+		// 存证续期的方法，expires_at为None时存证改为永久有效
+		#[pallet::weight(T::WeightInfo::renew_claim(claim.len() as u32))]
+		#[transactional]
+		pub fn renew_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+
+			// 改过期区块也能删除存证，共有存证不能由创建者一个人改
+			ensure!(!CoOwners::<T>::contains_key(&claim), Error::<T>::ClaimIsShared);
+
+			Self::ensure_not_frozen(&claim)?;
+
+			// 先从旧的清理队列里拿掉，再放进新的
+			Self::cancel_expiry(&claim);
+			if let Some(at) = expires_at {
+				Self::schedule_expiry(&claim, at)?;
+			}
+
+			Self::deposit_event(Event::ClaimRenewed(sender, claim, expires_at));
+
+			Ok(().into())
+		}
+
+		// 把一批文件的默克尔根作为一个存证，根固定是32字节，不受存证长度限制
+		#[pallet::weight(T::WeightInfo::create_merkle_claim())]
+		#[transactional]
+		pub fn create_merkle_claim(
+			origin: OriginFor<T>,
+			root: merkle::Hash,
+			leaf_count: u32,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let claim = root.to_vec();
+			Self::insert_claim(&sender, &claim, expires_at)?;
+			MerkleRoots::<T>::insert(&claim, leaf_count);
+
+			Self::deposit_event(Event::MerkleRootAnchored(sender, claim, leaf_count));
+
+			Ok(().into())
+		}
+
+		// 发起存证转移，接收者同意以后才真正转移
+		#[pallet::weight(T::WeightInfo::offer_claim(claim.len() as u32))]
+		pub fn offer_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			receiver: T::AccountId,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+
+			ensure!(owner != receiver, Error::<T>::OwnerEqualReceiver);
+
+			Self::ensure_not_frozen(&claim)?;
+
+			// 共有存证只能通过共有人同意转移
+			ensure!(!CoOwners::<T>::contains_key(&claim), Error::<T>::ClaimIsShared);
+
+			if let Some(at) = expires_at {
+				ensure!(
+					at > frame_system::Pallet::<T>::block_number(),
+					Error::<T>::InvalidExpiry
+				);
+			}
+
+			// 已有的转移请求会被覆盖
+			PendingOffers::<T>::insert(&claim, (receiver.clone(), expires_at));
+
+			Self::deposit_event(Event::ClaimOffered(sender, claim, receiver, expires_at));
+
+			Ok(().into())
+		}
+
+		// 接收者同意转移，存证和押金都转到接收者名下
+		#[pallet::weight(T::WeightInfo::accept_claim(claim.len() as u32))]
+		#[transactional]
+		pub fn accept_claim(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			let (receiver, expires_at) =
+				PendingOffers::<T>::get(&claim).ok_or(Error::<T>::OfferNotExist)?;
+
+			ensure!(receiver == sender, Error::<T>::NotOfferReceiver);
+
+			Self::ensure_not_frozen(&claim)?;
+
+			// 过期的转移请求不能再接受
+			if let Some(at) = expires_at {
+				ensure!(
+					frame_system::Pallet::<T>::block_number() < at,
+					Error::<T>::OfferExpired
+				);
+			}
+
+			Self::do_transfer_claim(&claim, &owner, &sender)?;
+
+			Self::deposit_event(Event::ClaimOfferAccepted(sender, claim, owner));
+
+			Ok(().into())
+		}
+
+		// 接收者拒绝转移
+		#[pallet::weight(T::WeightInfo::reject_claim(claim.len() as u32))]
+		pub fn reject_claim(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let (receiver, _) = PendingOffers::<T>::get(&claim).ok_or(Error::<T>::OfferNotExist)?;
+
+			ensure!(receiver == sender, Error::<T>::NotOfferReceiver);
+
+			PendingOffers::<T>::remove(&claim);
+
+			Self::deposit_event(Event::ClaimOfferRejected(sender, claim));
+
+			Ok(().into())
+		}
+
+		// 所有者取消转移请求
+		#[pallet::weight(T::WeightInfo::cancel_offer(claim.len() as u32))]
+		pub fn cancel_offer(origin: OriginFor<T>, claim: Vec<u8>) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			ensure!(owner == sender, Error::<T>::NotClaimOwner);
+
+			ensure!(PendingOffers::<T>::contains_key(&claim), Error::<T>::OfferNotExist);
+
+			PendingOffers::<T>::remove(&claim);
+
+			Self::deposit_event(Event::ClaimOfferCancelled(sender, claim));
+
+			Ok(().into())
+		}
+
+		// 创建共有存证，创建者自己也是共有人，删除和转移需要threshold个共有人同意
+		#[pallet::weight(
+			T::WeightInfo::create_shared_claim(claim.len() as u32, co_owners.len() as u32)
+		)]
+		#[transactional]
+		pub fn create_shared_claim(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			co_owners: Vec<T::AccountId>,
+			threshold: u32,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let mut owners = co_owners;
+			if !owners.contains(&sender) {
+				owners.insert(0, sender.clone());
+			}
+
+			for (index, owner) in owners.iter().enumerate() {
+				ensure!(!owners[..index].contains(owner), Error::<T>::DuplicateCoOwner);
+			}
+
+			ensure!(
+				threshold > 0 && threshold as usize <= owners.len(),
+				Error::<T>::InvalidThreshold
+			);
+
+			let owners = BoundedVec::<T::AccountId, T::MaxCoOwners>::try_from(owners)
+				.map_err(|_| Error::<T>::TooManyCoOwners)?;
+
+			Self::do_create_claim(&sender, claim.clone(), expires_at)?;
+			CoOwners::<T>::insert(&claim, (owners.clone(), threshold));
+
+			Self::deposit_event(Event::SharedClaimCreated(
+				sender,
+				claim,
+				owners.into_inner(),
+				threshold,
+			));
+
+			Ok(().into())
+		}
+
+		// 设置存证的描述信息，会覆盖原来的，空的描述信息表示删除。
+		// 押金和存证一样按基础押金加每字节押金算，由设置的人锁定，原来的押金返还
+		#[pallet::weight(T::WeightInfo::set_claim_metadata(
+			claim.len() as u32,
+			metadata.len() as u32,
+		))]
+		#[transactional]
+		pub fn set_claim_metadata(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			metadata: Vec<u8>,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			Self::ensure_owner(&claim, &owner, &sender)?;
+
+			Self::ensure_not_frozen(&claim)?;
+
+			let metadata = BoundedVec::<u8, T::MaxMetadataLen>::try_from(metadata)
+				.map_err(|_| Error::<T>::MetadataTooLong)?;
+
+			if metadata.is_empty() {
+				Self::clear_metadata(&claim);
+			} else {
+				let deposit = Self::deposit_for(metadata.len());
+				T::Currency::reserve(&sender, deposit)?;
+				Self::clear_metadata(&claim);
+				MetadataDeposits::<T>::insert(&claim, (sender.clone(), deposit));
+				ClaimMetadata::<T>::insert(&claim, metadata);
+			}
+
+			Self::index_claim(&claim);
+
+			Self::deposit_event(Event::ClaimMetadataSet(sender, claim));
+
+			Ok(().into())
+		}
+
+		// 强制删除存证，押金返还给所有者，只能由ForceOrigin调用
+		#[pallet::weight(T::WeightInfo::force_revoke(claim.len() as u32))]
+		pub fn force_revoke(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			reason: u8,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			let deposit = Self::remove_claim(&claim);
+			T::Currency::unreserve(&owner, deposit);
+
+			Self::deposit_event(Event::ClaimForceRevoked(owner, claim, reason));
+
+			Ok(().into())
+		}
+
+		// 强制转移存证，只能由ForceOrigin调用
+		#[pallet::weight(T::WeightInfo::force_transfer(claim.len() as u32))]
+		pub fn force_transfer(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			receiver: T::AccountId,
+			reason: u8,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			ensure!(owner != receiver, Error::<T>::OwnerEqualReceiver);
+
+			// 强制转移不能因为接收者钱不够而失败，所以直接把原所有者锁定的押金转成接收者锁定的押金
+			let deposit = ClaimDeposits::<T>::get(&claim);
+			let missing = T::Currency::repatriate_reserved(
+				&owner,
+				&receiver,
+				deposit,
+				BalanceStatus::Reserved,
+			)?;
+			ClaimDeposits::<T>::insert(&claim, deposit.saturating_sub(missing));
+
+			Self::set_owner(&claim, &owner, &receiver);
+			Self::close_dispute(&claim);
+
+			Self::deposit_event(Event::ClaimForceTransferred(owner, claim, receiver, reason));
+
+			Ok(().into())
+		}
+
+		// 对存证发起争议，锁定争议押金并冻结存证，直到ForceOrigin处理
+		#[pallet::weight(T::WeightInfo::open_dispute(claim.len() as u32))]
+		pub fn open_dispute(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			reason: u8,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(Proofs::<T>::contains_key(&claim), Error::<T>::ClaimNotExist);
+
+			ensure!(!Disputes::<T>::contains_key(&claim), Error::<T>::DisputeAlreadyOpen);
+
+			T::Currency::reserve(&sender, T::DisputeDeposit::get())?;
+
+			Disputes::<T>::insert(
 				&claim,
-				(receiver.clone(), frame_system::Pallet::<T>::block_number()),
+				(sender.clone(), reason, frame_system::Pallet::<T>::block_number()),
 			);
 
-			// 事件，表示sender把claim转移给receiver
-			Self::deposit_event(Event::ClaimTransfer(sender, claim, receiver));
+			Self::deposit_event(Event::DisputeOpened(sender, claim, reason));
+
+			Ok(().into())
+		}
+
+		// 处理争议：争议成立时删除存证并返还争议押金，不成立时罚没争议押金并解冻存证
+		#[pallet::weight(T::WeightInfo::resolve_dispute(claim.len() as u32))]
+		pub fn resolve_dispute(
+			origin: OriginFor<T>,
+			claim: Vec<u8>,
+			upheld: bool,
+		) -> DispatchResultWithPostInfo {
+			T::ForceOrigin::ensure_origin(origin)?;
+
+			let (opener, reason, _) =
+				Disputes::<T>::get(&claim).ok_or(Error::<T>::DisputeNotExist)?;
+
+			if upheld {
+				// remove_claim会关闭争议并返还争议押金
+				if let Some((owner, _)) = Proofs::<T>::get(&claim) {
+					let deposit = Self::remove_claim(&claim);
+					T::Currency::unreserve(&owner, deposit);
+					Self::deposit_event(Event::ClaimForceRevoked(owner, claim.clone(), reason));
+				}
+			} else {
+				Disputes::<T>::remove(&claim);
+				let _ = T::Currency::slash_reserved(&opener, T::DisputeDeposit::get());
+			}
+
+			Self::deposit_event(Event::DisputeResolved(claim, upheld));
+
+			Ok(().into())
+		}
+
+		// 批量创建存证的方法，每个存证都有自己的事件
+		#[pallet::weight(
+			T::WeightInfo::create_claims_batch(T::ClaimLimit::get() as u32, claims.len() as u32)
+		)]
+		#[transactional]
+		pub fn create_claims_batch(
+			origin: OriginFor<T>,
+			claims: Vec<Vec<u8>>,
+			expires_at: Option<T::BlockNumber>,
+			mode: BatchMode,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(claims.len() <= T::MaxBatchSize::get() as usize, Error::<T>::BatchTooLarge);
+
+			for claim in claims {
+				if let Err(e) = Self::do_create_claim(&sender, claim.clone(), expires_at) {
+					match mode {
+						// 整个交易回滚
+						BatchMode::AllOrNothing => return Err(e.into()),
+						// 只记一个失败的事件，接着处理下一个
+						BatchMode::BestEffort => Self::deposit_event(Event::ClaimCreateFailed(
+							sender.clone(),
+							claim,
+							e,
+						)),
+					}
+				}
+			}
+
+			Ok(().into())
+		}
+
+		// 批量删除存证的方法，每个存证都有自己的事件
+		#[pallet::weight(
+			T::WeightInfo::revoke_claims_batch(T::ClaimLimit::get() as u32, claims.len() as u32)
+		)]
+		#[transactional]
+		pub fn revoke_claims_batch(
+			origin: OriginFor<T>,
+			claims: Vec<Vec<u8>>,
+			mode: BatchMode,
+		) -> DispatchResultWithPostInfo {
+			let sender = ensure_signed(origin)?;
+
+			ensure!(claims.len() <= T::MaxBatchSize::get() as usize, Error::<T>::BatchTooLarge);
+
+			for claim in claims {
+				if let Err(e) = Self::do_revoke_claim(&sender, claim.clone()) {
+					match mode {
+						BatchMode::AllOrNothing => return Err(e.into()),
+						BatchMode::BestEffort => Self::deposit_event(Event::ClaimRevokeFailed(
+							sender.clone(),
+							claim,
+							e,
+						)),
+					}
+				}
+			}
 
 			Ok(().into())
 		}
 	}
+
+	impl<T: Config> Pallet<T> {
+		// 计算存证需要的押金：基础押金 + 每字节押金 * 存证长度
+		pub fn claim_deposit(claim: &[u8]) -> BalanceOf<T> {
+			Self::deposit_for(claim.len())
+		}
+
+		// 存证和描述信息的押金：基础押金 + 每字节押金 * 长度
+		fn deposit_for(len: usize) -> BalanceOf<T> {
+			let len: BalanceOf<T> = (len as u32).into();
+			T::ClaimDepositBase::get()
+				.saturating_add(T::ClaimDepositPerByte::get().saturating_mul(len))
+		}
+
+		// 删除描述信息并返还押金
+		fn clear_metadata(claim: &[u8]) {
+			ClaimMetadata::<T>::remove(claim);
+			if let Some((depositor, deposit)) = MetadataDeposits::<T>::take(claim) {
+				T::Currency::unreserve(&depositor, deposit);
+			}
+		}
+
+		// 创建一个存证，失败时这个存证的改动全部回滚
+		#[transactional]
+		fn do_create_claim(
+			sender: &T::AccountId,
+			claim: Vec<u8>,
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			ensure!(claim.len() <= T::ClaimLimit::get(), Error::<T>::ClaimLimitOver);
+
+			Self::insert_claim(sender, &claim, expires_at)?;
+
+			Self::deposit_event(Event::ClaimCreated(sender.clone(), claim));
+
+			Ok(())
+		}
+
+		// 保存存证并锁定押金，不检查存证长度
+		fn insert_claim(
+			sender: &T::AccountId,
+			claim: &[u8],
+			expires_at: Option<T::BlockNumber>,
+		) -> DispatchResult {
+			ensure!(!Proofs::<T>::contains_key(claim), Error::<T>::ProofAlreadyExist);
+			// 迁移期间还没搬过来的v2存证也算已经存在
+			ensure!(
+				!crate::migrations::is_pending_v2_claim::<T>(claim),
+				Error::<T>::ProofAlreadyExist
+			);
+
+			// 按存证长度锁定押金，钱不够就不能创建
+			let deposit = Self::claim_deposit(claim);
+			T::Currency::reserve(sender, deposit)?;
+
+			Proofs::<T>::insert(claim, (sender.clone(), frame_system::Pallet::<T>::block_number()));
+			OwnerClaims::<T>::insert(sender, claim, ());
+			ClaimDeposits::<T>::insert(claim, deposit);
+
+			// 设置了过期区块的存证放进清理队列
+			if let Some(at) = expires_at {
+				Self::schedule_expiry(claim, at)?;
+			}
+
+			Self::index_claim(claim);
+
+			Ok(())
+		}
+
+		// 转移存证，押金跟着存证走：先锁定接收者的押金，成功后再返还给发送者
+		fn do_transfer_claim(
+			claim: &[u8],
+			from: &T::AccountId,
+			to: &T::AccountId,
+		) -> DispatchResult {
+			let deposit = ClaimDeposits::<T>::get(claim);
+			T::Currency::reserve(to, deposit)?;
+			T::Currency::unreserve(from, deposit);
+
+			Self::set_owner(claim, from, to);
+
+			Ok(())
+		}
+
+		// 修改存证的所有者，同时更新所有者索引、变更记录和转移请求
+		fn set_owner(claim: &[u8], from: &T::AccountId, to: &T::AccountId) {
+			// 保存新的存证，存证所有者为接收者参数的值，会直接覆盖旧值，map修改值的方法有2:insert and mutate
+			Proofs::<T>::insert(claim, (to.clone(), frame_system::Pallet::<T>::block_number()));
+			OwnerClaims::<T>::remove(from, claim);
+			OwnerClaims::<T>::insert(to, claim, ());
+
+			Self::record_ownership_change(claim, from, to);
+
+			// 所有者变了，原来的转移请求和共有关系都作废
+			PendingOffers::<T>::remove(claim);
+			Self::clear_co_owners(claim);
+
+			Self::index_claim(claim);
+		}
+
+		// 普通存证只有所有者能操作，共有存证每个共有人都能操作
+		fn ensure_owner(claim: &[u8], owner: &T::AccountId, who: &T::AccountId) -> DispatchResult {
+			match CoOwners::<T>::get(claim) {
+				Some((owners, _)) => ensure!(owners.contains(who), Error::<T>::NotClaimOwner),
+				None => ensure!(owner == who, Error::<T>::NotClaimOwner),
+			}
+			Ok(())
+		}
+
+		// 记录共有人对操作的同意，同意数达到门槛时返回true，普通存证直接返回true
+		fn approve(
+			claim: &[u8],
+			who: &T::AccountId,
+			action: ClaimAction<T::AccountId>,
+		) -> Result<bool, DispatchError> {
+			let threshold = match CoOwners::<T>::get(claim) {
+				Some((_, threshold)) => threshold,
+				None => return Ok(true),
+			};
+
+			let approvals = PendingApprovals::<T>::try_mutate(
+				claim,
+				&action,
+				|approvals| -> Result<u32, DispatchError> {
+					ensure!(!approvals.contains(who), Error::<T>::AlreadyApproved);
+					approvals.try_push(who.clone()).map_err(|_| Error::<T>::TooManyCoOwners)?;
+
+					// 新的待执行操作由第一个同意的共有人锁定押金
+					if approvals.len() == 1 {
+						let deposit = T::ApprovalDeposit::get();
+						T::Currency::reserve(who, deposit)?;
+						ApprovalDeposits::<T>::insert(claim, &action, (who.clone(), deposit));
+					}
+
+					Ok(approvals.len() as u32)
+				},
+			)?;
+
+			Self::deposit_event(Event::ApprovalRecorded(who.clone(), claim.to_vec(), approvals));
+
+			Ok(approvals >= threshold)
+		}
+
+		// 删除共有关系和所有待执行操作的同意记录，返还待执行操作的押金
+		fn clear_co_owners(claim: &[u8]) {
+			if CoOwners::<T>::take(claim).is_some() {
+				let _ = PendingApprovals::<T>::remove_prefix(claim, None);
+				for (_, (depositor, deposit)) in ApprovalDeposits::<T>::drain_prefix(claim) {
+					T::Currency::unreserve(&depositor, deposit);
+				}
+			}
+		}
+
+		// 有争议的存证被冻结，不能修改
+		fn ensure_not_frozen(claim: &[u8]) -> DispatchResult {
+			ensure!(!Disputes::<T>::contains_key(claim), Error::<T>::ClaimFrozen);
+			Ok(())
+		}
+
+		// 关闭争议并返还发起人的争议押金
+		fn close_dispute(claim: &[u8]) {
+			if let Some((opener, _, _)) = Disputes::<T>::take(claim) {
+				T::Currency::unreserve(&opener, T::DisputeDeposit::get());
+			}
+		}
+
+		// 记录一次所有者变更，超过上限时丢掉最早的记录
+		fn record_ownership_change(claim: &[u8], from: &T::AccountId, to: &T::AccountId) {
+			let change = (from.clone(), to.clone(), frame_system::Pallet::<T>::block_number());
+
+			ClaimHistory::<T>::mutate(claim, |history| {
+				let mut changes = sp_std::mem::take(history).into_inner();
+				if !changes.is_empty() && changes.len() >= T::MaxHistoryLen::get() as usize {
+					changes.remove(0);
+				}
+				changes.push(change);
+				*history = BoundedVec::try_from(changes).unwrap_or_default();
+			});
+		}
+
+		// 删除存证相关的所有数据，返回押金数额
+		fn remove_claim(claim: &[u8]) -> BalanceOf<T> {
+			if let Some((owner, _)) = Proofs::<T>::take(claim) {
+				OwnerClaims::<T>::remove(&owner, claim);
+			}
+			PendingOffers::<T>::remove(claim);
+			ClaimHistory::<T>::remove(claim);
+			Self::clear_co_owners(claim);
+			Self::close_dispute(claim);
+			MerkleRoots::<T>::remove(claim);
+			Self::clear_metadata(claim);
+			Self::cancel_expiry(claim);
+
+			Self::index_claim(claim);
+
+			ClaimDeposits::<T>::take(claim)
+		}
+
+		// 记下这个区块里有变化的存证，区块导入后由链下工作机写进链下索引
+		pub(crate) fn index_claim(claim: &[u8]) {
+			IndexedClaims::<T>::mutate(frame_system::Pallet::<T>::block_number(), |claims| {
+				if !claims.iter().any(|c| c.as_slice() == claim) {
+					claims.push(claim.to_vec());
+				}
+			});
+		}
+
+		// 在区块n的状态上读出有变化的存证现在的所有者、区块和描述信息，连同区块哈希写进链下索引，
+		// 所有者为None的记录表示存证已删除
+		fn write_index(n: T::BlockNumber) {
+			let claims = IndexedClaims::<T>::get(n);
+			if claims.is_empty() {
+				return
+			}
+
+			let records = claims
+				.into_iter()
+				.map(|claim| {
+					let (owner, block_number) = match Proofs::<T>::get(&claim) {
+						Some((owner, block_number)) => (Some(owner), block_number),
+						None => (None, n),
+					};
+					let metadata = ClaimMetadata::<T>::get(&claim).into_inner();
+					index::ClaimRecord { claim, owner, block_number, metadata }
+				})
+				.collect();
+
+			index::write(n, frame_system::Pallet::<T>::block_hash(n), records);
+		}
+
+		// 分页列出账户拥有的存证和存证的区块，每页最多MAX_CLAIMS_PER_PAGE个
+		pub fn claims_of(
+			owner: &T::AccountId,
+			start: u32,
+			limit: u32,
+		) -> Vec<(Vec<u8>, T::BlockNumber)> {
+			OwnerClaims::<T>::iter_prefix(owner)
+				.skip(start as usize)
+				.take(limit.min(MAX_CLAIMS_PER_PAGE) as usize)
+				.filter_map(|(claim, _)| Proofs::<T>::get(&claim).map(|(_, block)| (claim, block)))
+				.collect()
+		}
+
+		// 校验叶子是否包含在一个已存证的默克尔根里，证明长度要和存证时的叶子数对得上
+		pub fn verify_inclusion(
+			root: &merkle::Hash,
+			leaf: &merkle::Hash,
+			proof: &[merkle::Hash],
+		) -> bool {
+			MerkleRoots::<T>::get(root.to_vec())
+				.map_or(false, |leaf_count| merkle::verify_proof(root, leaf, proof, leaf_count))
+		}
+
+		// 删除一个存证并返还押金
+		fn do_revoke_claim(sender: &T::AccountId, claim: Vec<u8>) -> DispatchResult {
+			let (owner, _) = Proofs::<T>::get(&claim).ok_or(Error::<T>::ClaimNotExist)?;
+
+			Self::ensure_owner(&claim, &owner, sender)?;
+
+			Self::ensure_not_frozen(&claim)?;
+
+			// 共有存证同意数不够时只记录同意，先不删除
+			if !Self::approve(&claim, sender, ClaimAction::Revoke)? {
+				return Ok(())
+			}
+
+			// 押金返还给锁定押金的所有者
+			let deposit = Self::remove_claim(&claim);
+			T::Currency::unreserve(&owner, deposit);
+
+			Self::deposit_event(Event::ClaimRevoked(owner, claim));
+
+			Ok(())
+		}
+
+		// 把存证放进过期区块的清理队列，过期区块必须在当前区块之后
+		fn schedule_expiry(claim: &[u8], at: T::BlockNumber) -> DispatchResult {
+			ensure!(at > frame_system::Pallet::<T>::block_number(), Error::<T>::InvalidExpiry);
+
+			ExpiryQueue::<T>::try_mutate(at, |queue| {
+				queue.try_push(claim.to_vec()).map_err(|_| Error::<T>::ExpiryQueueFull)
+			})?;
+			ClaimExpiry::<T>::insert(claim, at);
+
+			Ok(())
+		}
+
+		// 把存证从清理队列里拿掉
+		fn cancel_expiry(claim: &[u8]) {
+			if let Some(at) = ClaimExpiry::<T>::take(claim) {
+				ExpiryQueue::<T>::mutate(at, |queue| queue.retain(|c| c.as_slice() != claim));
+			}
+		}
+
+		// 把存证放进now之后第一个清理队列还有空位的区块
+		fn defer_expiry(claim: Vec<u8>, now: T::BlockNumber) {
+			let mut at = now;
+			loop {
+				at = at.saturating_add(1u32.into());
+				if ExpiryQueue::<T>::mutate(at, |queue| queue.try_push(claim.clone()).is_ok()) {
+					ClaimExpiry::<T>::insert(&claim, at);
+					return
+				}
+			}
+		}
+
+		// 删除过期的存证并返还押金
+		fn expire_claim(claim: Vec<u8>, now: T::BlockNumber) {
+			// 只处理确实在这个区块过期的存证
+			if ClaimExpiry::<T>::get(&claim) != Some(now) {
+				return
+			}
+
+			// 有争议的存证冻结着，先不删除，挪到下一个区块，争议处理完以后再过期
+			if Disputes::<T>::contains_key(&claim) {
+				Self::defer_expiry(claim, now);
+				return
+			}
+
+			if let Some((owner, _)) = Proofs::<T>::get(&claim) {
+				let deposit = Self::remove_claim(&claim);
+				T::Currency::unreserve(&owner, deposit);
+
+				Self::deposit_event(Event::ClaimExpired(owner, claim));
+			}
+		}
+	}
 }
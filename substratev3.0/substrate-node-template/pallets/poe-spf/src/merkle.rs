@@ -0,0 +1,96 @@
+//! 默克尔树工具：链上用来校验包含证明，节点命令行用来生成树和证明。
+//!
+//! 叶子是 0x00 加上文件内容的 blake2_256 哈希。父节点是 0x01 加上两个子节点按字节序排序后的拼接，
+//! 再做 blake2_256，所以证明里不需要记录左右位置。叶子和父节点的前缀不同，
+//! 两个子节点拼起来的64字节“文件”算不出父节点的哈希。
+//! 某一层的节点数是奇数时，最后一个节点和自己配对，所以每个叶子的证明长度都是 ceil(log2(叶子数))。
+
+use sp_io::hashing::blake2_256;
+use sp_std::vec::Vec;
+
+/// 默克尔树里节点的哈希
+pub type Hash = [u8; 32];
+
+// 叶子和父节点哈希前的前缀
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+/// 文件内容对应的叶子
+pub fn hash_leaf(data: &[u8]) -> Hash {
+	let mut buf = Vec::with_capacity(1 + data.len());
+	buf.push(LEAF_PREFIX);
+	buf.extend_from_slice(data);
+	blake2_256(&buf)
+}
+
+// 两个子节点排序后拼接再哈希
+fn hash_pair(a: &Hash, b: &Hash) -> Hash {
+	let (first, second) = if a <= b { (a, b) } else { (b, a) };
+	let mut buf = [0u8; 65];
+	buf[0] = NODE_PREFIX;
+	buf[1..33].copy_from_slice(first);
+	buf[33..].copy_from_slice(second);
+	blake2_256(&buf)
+}
+
+// 由下一层的节点算出上一层的节点
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+	level
+		.chunks(2)
+		.map(|pair| match pair {
+			[a, b] => hash_pair(a, b),
+			[a] => hash_pair(a, a),
+			_ => unreachable!("chunks(2) yields one or two items; qed"),
+		})
+		.collect()
+}
+
+/// 有 leaf_count 个叶子的树里，每个证明的长度，没有叶子时返回 None
+pub fn proof_len(leaf_count: u32) -> Option<usize> {
+	if leaf_count == 0 {
+		return None
+	}
+	Some(u64::from(leaf_count).next_power_of_two().trailing_zeros() as usize)
+}
+
+/// 计算默克尔根，没有叶子时返回 None
+pub fn merkle_root(leaves: &[Hash]) -> Option<Hash> {
+	if leaves.is_empty() {
+		return None
+	}
+
+	let mut level = leaves.to_vec();
+	while level.len() > 1 {
+		level = next_level(&level);
+	}
+	Some(level[0])
+}
+
+/// 生成第 index 个叶子的包含证明，index 越界时返回 None
+pub fn merkle_proof(leaves: &[Hash], index: usize) -> Option<Vec<Hash>> {
+	if index >= leaves.len() {
+		return None
+	}
+
+	let mut proof = Vec::new();
+	let mut level = leaves.to_vec();
+	let mut index = index;
+	while level.len() > 1 {
+		// 兄弟节点不存在时，这个节点和自己配对
+		let sibling = (index ^ 1).min(level.len() - 1);
+		proof.push(level[sibling]);
+		level = next_level(&level);
+		index /= 2;
+	}
+	Some(proof)
+}
+
+/// 校验叶子是否包含在以 root 为根、有 leaf_count 个叶子的默克尔树里，证明长度不对的不通过
+pub fn verify_proof(root: &Hash, leaf: &Hash, proof: &[Hash], leaf_count: u32) -> bool {
+	if proof_len(leaf_count) != Some(proof.len()) {
+		return false
+	}
+
+	let computed = proof.iter().fold(*leaf, |acc, node| hash_pair(&acc, node));
+	&computed == root
+}
@@ -0,0 +1,119 @@
+//! 存储迁移：把 v2 节点里的存证搬到 v3 的存储里。
+//!
+//! v2 的 poe-spf 用 `decl_storage!` 把存证存在 `TemplateModule` 前缀下的 `Proofs` 里，
+//! 值是 (所有者, 区块)，键用 blake2_128_concat。v3 的存证在本模块自己的前缀下，
+//! 另外还要有按所有者的索引。
+//!
+//! v2 的存证没有锁定押金，搬过来时按存证长度从所有者那里锁定押金，和新建存证一样；
+//! 所有者的钱不够时存证照样保留，押金记为 0，删除时也不返还。
+//!
+//! 存证可能很多，一个区块最多搬 [`MIGRATION_BATCH`] 个：升级时搬第一批，
+//! 剩下的在之后每个区块的 `on_initialize` 里接着搬，全部搬完才把版本改成 V3。
+//! 迁移期间还没搬过来的存证仍然属于 v2 的所有者，不能被别人抢先创建。
+
+use codec::Encode;
+use crate::{ClaimDeposits, Config, OwnerClaims, Pallet, Proofs, Releases, StorageVersion};
+use frame_support::{
+	storage::{
+		migration::{have_storage_value, storage_iter, storage_key_iter},
+		PrefixIterator,
+	},
+	traits::{Get, ReservableCurrency},
+	weights::Weight,
+	Blake2_128Concat, StorageHasher,
+};
+use sp_std::vec::Vec;
+
+/// 一个区块最多迁移的 v2 存证数
+pub const MIGRATION_BATCH: usize = 64;
+
+// v2 存证所在的模块前缀和存储名
+const V2_MODULE: &[u8] = b"TemplateModule";
+const V2_PROOFS: &[u8] = b"Proofs";
+
+// 遍历 v2 的存证，解码失败的会被跳过
+fn v2_proofs<T: Config>() -> PrefixIterator<(Vec<u8>, (T::AccountId, T::BlockNumber))> {
+	storage_key_iter::<Vec<u8>, (T::AccountId, T::BlockNumber), Blake2_128Concat>(
+		V2_MODULE, V2_PROOFS,
+	)
+}
+
+/// 迁移还没完成并且存证还在 v2 的存储里
+pub(crate) fn is_pending_v2_claim<T: Config>(claim: &[u8]) -> bool {
+	StorageVersion::<T>::get() == Releases::V2 &&
+		have_storage_value(V2_MODULE, V2_PROOFS, &Blake2_128Concat::hash(&claim.encode()))
+}
+
+/// 把最多 [`MIGRATION_BATCH`] 个 v2 的存证搬到 v3，全部搬完后版本改成 V3，已经是 v3 时什么都不做
+pub fn migrate_to_v3<T: Config>() -> Weight {
+	if StorageVersion::<T>::get() != Releases::V2 {
+		return T::DbWeight::get().reads(1)
+	}
+
+	let mut moved: Weight = 0;
+	let mut skipped: Weight = 0;
+	for (claim, (owner, block_number)) in v2_proofs::<T>().drain().take(MIGRATION_BATCH) {
+		// v3 里已经有同样的存证时保留 v3 的
+		if Proofs::<T>::contains_key(&claim) {
+			skipped += 1;
+			continue
+		}
+
+		// 钱不够锁定押金的存证不收押金
+		let deposit = Pallet::<T>::claim_deposit(&claim);
+		if T::Currency::reserve(&owner, deposit).is_ok() {
+			ClaimDeposits::<T>::insert(&claim, deposit);
+		}
+
+		OwnerClaims::<T>::insert(&owner, &claim, ());
+		Proofs::<T>::insert(&claim, (owner, block_number));
+		Pallet::<T>::index_claim(&claim);
+		moved += 1;
+	}
+
+	// 没有剩下的 v2 存证了才算迁移完成
+	let done = storage_iter::<()>(V2_MODULE, V2_PROOFS).next().is_none();
+	if done {
+		StorageVersion::<T>::put(Releases::V3);
+	}
+
+	let count = moved + skipped;
+	T::DbWeight::get().reads_writes(2 + 2 * count + 3 * moved, done as Weight + count + 5 * moved)
+}
+
+/// 迁移前的检查：v2 的存证都能解码，不会在迁移时丢掉
+#[cfg(any(feature = "try-runtime", test))]
+pub fn pre_migrate_to_v3<T: Config>() -> Result<(), &'static str> {
+	if StorageVersion::<T>::get() != Releases::V2 {
+		return Ok(())
+	}
+
+	// 值解码成 () 总是成功，用来数 v2 里一共有多少条记录
+	let total = storage_iter::<()>(V2_MODULE, V2_PROOFS).count();
+	frame_support::ensure!(v2_proofs::<T>().count() == total, "undecodable v2 proofs");
+
+	Ok(())
+}
+
+/// 迁移后的检查：版本是 V3 时 v2 的存证都被删掉了，已经搬过来的存证都有所有者索引
+#[cfg(any(feature = "try-runtime", test))]
+pub fn post_migrate_to_v3<T: Config>() -> Result<(), &'static str> {
+	let left = storage_iter::<()>(V2_MODULE, V2_PROOFS).next().is_some();
+	frame_support::ensure!(
+		StorageVersion::<T>::get() == Releases::V3 || left,
+		"storage version not V3"
+	);
+	frame_support::ensure!(
+		StorageVersion::<T>::get() == Releases::V2 || !left,
+		"v2 proofs left after migration"
+	);
+
+	for (claim, (owner, _)) in Proofs::<T>::iter() {
+		frame_support::ensure!(
+			OwnerClaims::<T>::contains_key(&owner, &claim),
+			"proof missing from the owner index"
+		);
+	}
+
+	Ok(())
+}
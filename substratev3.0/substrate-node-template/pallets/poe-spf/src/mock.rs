@@ -1,6 +1,7 @@
 use crate as pallet_poe_spf;
-use frame_support::parameter_types;
+use frame_support::{parameter_types, traits::GenesisBuild};
 use frame_system as system;
+use frame_system::EnsureRoot;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
@@ -18,13 +19,15 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
-		PoeSpfModule: pallet_poe_spf::{Pallet, Call, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		PoeSpfModule: pallet_poe_spf::{Pallet, Call, Storage, Config, Event<T>},
 	}
 );
 
 parameter_types! {
 	pub const BlockHashCount: u64 = 250;
 	pub const SS58Prefix: u8 = 42;
+	pub const ExistentialDeposit: u64 = 1;
 }
 
 impl system::Config for Test {
@@ -45,7 +48,7 @@ impl system::Config for Test {
 	type BlockHashCount = BlockHashCount;
 	type Version = ();
 	type PalletInfo = PalletInfo;
-	type AccountData = ();
+	type AccountData = pallet_balances::AccountData<u64>;
 	type OnNewAccount = ();
 	type OnKilledAccount = ();
 	type SystemWeightInfo = ();
@@ -53,16 +56,58 @@ impl system::Config for Test {
 	type OnSetCode = ();
 }
 
+impl pallet_balances::Config for Test {
+	type Balance = u64;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type WeightInfo = ();
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+}
+
 parameter_types! {
 	pub const ClaimLimit: usize = 4; // 存证长度限制
+	pub const ClaimDepositBase: u64 = 10; // 每个存证的基础押金
+	pub const ClaimDepositPerByte: u64 = 1; // 存证每个字节的押金
+	pub const MaxExpiringPerBlock: u32 = 2; // 每个区块最多过期的存证数
+	pub const MaxBatchSize: u32 = 3; // 批量操作最多的存证数
+	pub const MaxHistoryLen: u32 = 2; // 每个存证最多保留的所有者变更记录数
+	pub const DisputeDeposit: u64 = 5; // 发起争议的押金
+	pub const MaxCoOwners: u32 = 3; // 共有存证最多的共有人数
+	pub const ApprovalDeposit: u64 = 2; // 共有存证每个待执行操作的押金
+	pub const MaxMetadataLen: u32 = 8; // 存证描述信息的最大长度
 }
 
 impl pallet_poe_spf::Config for Test {
 	type Event = Event;
 	type ClaimLimit = ClaimLimit;
+	type Currency = Balances;
+	type ClaimDepositBase = ClaimDepositBase;
+	type ClaimDepositPerByte = ClaimDepositPerByte;
+	type MaxExpiringPerBlock = MaxExpiringPerBlock;
+	type MaxBatchSize = MaxBatchSize;
+	type MaxHistoryLen = MaxHistoryLen;
+	type ForceOrigin = EnsureRoot<u64>;
+	type DisputeDeposit = DisputeDeposit;
+	type MaxCoOwners = MaxCoOwners;
+	type ApprovalDeposit = ApprovalDeposit;
+	type MaxMetadataLen = MaxMetadataLen;
+	type WeightInfo = ();
 }
 
 // Build genesis storage according to the mock runtime.
 pub fn new_test_ext() -> sp_io::TestExternalities {
-	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
+	let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+	// 用户1、2、9有足够的钱，用户3的钱不够交押金
+	pallet_balances::GenesisConfig::<Test> {
+		balances: vec![(1, 100), (2, 100), (3, 5), (9, 100)],
+	}
+	.assimilate_storage(&mut t)
+	.unwrap();
+	GenesisBuild::<Test>::assimilate_storage(&pallet_poe_spf::GenesisConfig::default(), &mut t)
+		.unwrap();
+	t.into()
 }
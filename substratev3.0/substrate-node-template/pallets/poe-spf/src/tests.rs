@@ -1,6 +1,34 @@
 use super::*;
+use super::Event as PoeEvent;
 use crate::{mock::*, Error};
-use frame_support::{assert_noop, assert_ok};
+use codec::Encode;
+use frame_support::{
+    assert_noop, assert_ok,
+    storage::migration::{get_storage_value, put_storage_value},
+    traits::{OffchainWorker, OnFinalize, OnInitialize, OnRuntimeUpgrade, ReservableCurrency},
+    Blake2_128Concat, StorageHasher,
+};
+use index::{ClaimFilter, ClaimRecord};
+use migrations::{post_migrate_to_v3, pre_migrate_to_v3, MIGRATION_BATCH};
+use sp_core::{
+    offchain::{testing::TestOffchainExt, OffchainDbExt, OffchainWorkerExt, StorageKind},
+    H256,
+};
+
+// 测试里第n个区块的哈希
+fn block_hash(n: u64) -> H256 {
+    H256::from_low_u64_be(n + 1)
+}
+
+// 一个区块一个区块地往前走，每个区块都执行on_finalize和on_initialize
+fn run_to_block(n: u64) {
+    while System::block_number() < n {
+        PoeSpfModule::on_finalize(System::block_number());
+        System::set_parent_hash(block_hash(System::block_number()));
+        System::set_block_number(System::block_number() + 1);
+        PoeSpfModule::on_initialize(System::block_number());
+    }
+}
 
 // 测试创建存证
 #[test]
@@ -8,7 +36,7 @@ fn create_claim_works() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         // 测试创建存证
-        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
         assert_eq!(
             Proofs::<Test>::get(&claim),
             Some((1, frame_system::Pallet::<Test>::block_number()))
@@ -24,7 +52,7 @@ fn create_claim_failed_when_claim_limit_over() {
         let claim = vec![0, 1, 2, 3, 4];
         // 先创建一个存证
         assert_noop!(
-            PoeSpfModule::create_claim(Origin::signed(1), claim.clone()),
+            PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None),
             Error::<Test>::ClaimLimitOver
         );
     })
@@ -36,10 +64,10 @@ fn create_claim_failed_when_claim_already_exist() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         // 先创建一个存证
-        let _ = PoeSpfModule::create_claim(Origin::signed(1), claim.clone());
+        let _ = PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None);
         // 创建一个重复的存证
         assert_noop!(
-            PoeSpfModule::create_claim(Origin::signed(1), claim),
+            PoeSpfModule::create_claim(Origin::signed(1), claim, None),
             Error::<Test>::ProofAlreadyExist
         );
     })
@@ -51,7 +79,7 @@ fn revoke_claim_works() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         // 先创建一个存证
-        let _ = PoeSpfModule::create_claim(Origin::signed(1), claim.clone());
+        let _ = PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None);
 
         // 删除存证
         assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), claim.clone()));
@@ -79,7 +107,7 @@ fn revoke_claim_failed_when_claim_is_not_oneself() {
         // 测试删除一个不存在的存证
         let claim = vec![0, 1];
         // 先创建一个存证
-        let _ = PoeSpfModule::create_claim(Origin::signed(1), claim.clone());
+        let _ = PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None);
 
         assert_noop!(
             PoeSpfModule::revoke_claim(Origin::signed(2), claim.clone()),
@@ -94,7 +122,7 @@ fn transfer_claim_works() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         // 先创建一个存证
-        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
         assert_eq!(
             Proofs::<Test>::get(&claim),
             Some((1, frame_system::Pallet::<Test>::block_number()))
@@ -118,7 +146,7 @@ fn transfer_claim_when_already_transfer() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         // 先创建一个存证
-        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
         assert_eq!(
             Proofs::<Test>::get(&claim),
             Some((1, frame_system::Pallet::<Test>::block_number()))
@@ -139,7 +167,7 @@ fn transfer_claim_when_2_address_equal() {
     new_test_ext().execute_with(|| {
         let claim = vec![0, 1];
         // 先创建一个存证
-        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
         assert_eq!(
             Proofs::<Test>::get(&claim),
             Some((1, frame_system::Pallet::<Test>::block_number()))
@@ -151,3 +179,1535 @@ fn transfer_claim_when_2_address_equal() {
         );
     })
 }
+
+// 创建存证时锁定押金：基础押金10 + 每字节1 * 2字节
+#[test]
+fn create_claim_reserves_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_eq!(Balances::reserved_balance(1), 12);
+        assert_eq!(Balances::free_balance(1), 88);
+        assert_eq!(ClaimDeposits::<Test>::get(&claim), 12);
+    })
+}
+
+// 钱不够交押金时不能创建存证
+#[test]
+fn create_claim_failed_when_deposit_not_enough() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_noop!(
+            PoeSpfModule::create_claim(Origin::signed(3), claim, None),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+    })
+}
+
+// 删除存证时返还押金
+#[test]
+fn revoke_claim_unreserves_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 100);
+        assert_eq!(ClaimDeposits::<Test>::get(&claim), 0);
+    })
+}
+
+// 转移存证时押金跟着存证走：接收者锁定押金，发送者的押金返还
+#[test]
+fn transfer_claim_moves_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 9));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 100);
+        assert_eq!(Balances::reserved_balance(9), 12);
+        assert_eq!(Balances::free_balance(9), 88);
+    })
+}
+
+// 接收者钱不够交押金时不能转移
+#[test]
+fn transfer_claim_failed_when_receiver_deposit_not_enough() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::transfer_claim(Origin::signed(1), claim, 3),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+    })
+}
+
+// 创建带过期区块的存证，到期后自动删除并返还押金
+#[test]
+fn claim_expires_at_expiry_block() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), Some(3)));
+        assert_eq!(ClaimExpiry::<Test>::get(&claim), Some(3));
+        assert_eq!(ExpiryQueue::<Test>::get(3).into_inner(), vec![claim.clone()]);
+
+        // 还没到期，存证还在
+        run_to_block(2);
+        assert!(Proofs::<Test>::contains_key(&claim));
+
+        // 到期了，存证被删除，押金返还
+        run_to_block(3);
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+        assert_eq!(ClaimExpiry::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimExpired(1, claim).into()
+        );
+    })
+}
+
+// 过期区块不能是当前区块或者之前的区块
+#[test]
+fn create_claim_failed_when_expiry_in_past() {
+    new_test_ext().execute_with(|| {
+        run_to_block(5);
+        assert_noop!(
+            PoeSpfModule::create_claim(Origin::signed(1), vec![0, 1], Some(5)),
+            Error::<Test>::InvalidExpiry
+        );
+    })
+}
+
+// 同一个区块过期的存证数超过限制
+#[test]
+fn create_claim_failed_when_expiry_queue_full() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0], Some(3)));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![1], Some(3)));
+        assert_noop!(
+            PoeSpfModule::create_claim(Origin::signed(1), vec![2], Some(3)),
+            Error::<Test>::ExpiryQueueFull
+        );
+    })
+}
+
+// 续期后存证在新的区块过期
+#[test]
+fn renew_claim_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), Some(3)));
+        assert_ok!(PoeSpfModule::renew_claim(Origin::signed(1), claim.clone(), Some(5)));
+        assert_eq!(ClaimExpiry::<Test>::get(&claim), Some(5));
+        assert!(ExpiryQueue::<Test>::get(3).is_empty());
+
+        // 原来的过期区块不再删除存证
+        run_to_block(4);
+        assert!(Proofs::<Test>::contains_key(&claim));
+
+        run_to_block(5);
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+    })
+}
+
+// 续期为None时存证永久有效
+#[test]
+fn renew_claim_to_never_expire() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), Some(3)));
+        assert_ok!(PoeSpfModule::renew_claim(Origin::signed(1), claim.clone(), None));
+        assert_eq!(ClaimExpiry::<Test>::get(&claim), None);
+
+        run_to_block(10);
+        assert!(Proofs::<Test>::contains_key(&claim));
+    })
+}
+
+// 只有所有者才能续期
+#[test]
+fn renew_claim_failed_when_not_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::renew_claim(Origin::signed(2), claim, Some(5)),
+            Error::<Test>::NotClaimOwner
+        );
+    })
+}
+
+// 删除存证时也从清理队列里拿掉
+#[test]
+fn revoke_claim_cancels_expiry() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), Some(3)));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_eq!(ClaimExpiry::<Test>::get(&claim), None);
+        assert!(ExpiryQueue::<Test>::get(3).is_empty());
+    })
+}
+
+// 批量创建存证，每个存证都有自己的事件
+#[test]
+fn create_claims_batch_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claims = vec![vec![0], vec![1], vec![2]];
+        assert_ok!(PoeSpfModule::create_claims_batch(
+            Origin::signed(1),
+            claims.clone(),
+            None,
+            BatchMode::AllOrNothing
+        ));
+        for claim in claims.iter() {
+            assert_eq!(Proofs::<Test>::get(claim), Some((1, 1)));
+            assert!(System::events().iter().any(|r| r.event
+                == PoeEvent::ClaimCreated(1, claim.clone()).into()));
+        }
+        // 每个存证押金11
+        assert_eq!(Balances::reserved_balance(1), 33);
+    })
+}
+
+// 批量创建时有一个失败，全部回滚
+#[test]
+fn create_claims_batch_all_or_nothing_rolls_back() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(2), vec![1], None));
+        assert_noop!(
+            PoeSpfModule::create_claims_batch(
+                Origin::signed(1),
+                vec![vec![0], vec![1], vec![2]],
+                None,
+                BatchMode::AllOrNothing
+            ),
+            Error::<Test>::ProofAlreadyExist
+        );
+    })
+}
+
+// 批量创建时跳过失败的存证，保留成功的
+#[test]
+fn create_claims_batch_best_effort_skips_failures() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(2), vec![1], None));
+        assert_ok!(PoeSpfModule::create_claims_batch(
+            Origin::signed(1),
+            vec![vec![0], vec![1], vec![2, 3, 4, 5, 6]],
+            None,
+            BatchMode::BestEffort
+        ));
+        assert_eq!(Proofs::<Test>::get(vec![0]), Some((1, 1)));
+        assert_eq!(Proofs::<Test>::get(vec![1]), Some((2, 1)));
+        assert_eq!(Proofs::<Test>::get(vec![2, 3, 4, 5, 6]), None);
+        // 只有成功的存证锁定了押金
+        assert_eq!(Balances::reserved_balance(1), 11);
+        assert!(System::events().iter().any(|r| r.event
+            == PoeEvent::ClaimCreateFailed(1, vec![1], Error::<Test>::ProofAlreadyExist.into())
+                .into()));
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimCreateFailed(
+                1,
+                vec![2, 3, 4, 5, 6],
+                Error::<Test>::ClaimLimitOver.into()
+            )
+            .into()
+        );
+    })
+}
+
+// 批量操作的存证数超过限制
+#[test]
+fn claims_batch_failed_when_too_large() {
+    new_test_ext().execute_with(|| {
+        let claims = vec![vec![0], vec![1], vec![2], vec![3]];
+        assert_noop!(
+            PoeSpfModule::create_claims_batch(
+                Origin::signed(1),
+                claims.clone(),
+                None,
+                BatchMode::BestEffort
+            ),
+            Error::<Test>::BatchTooLarge
+        );
+        assert_noop!(
+            PoeSpfModule::revoke_claims_batch(Origin::signed(1), claims, BatchMode::BestEffort),
+            Error::<Test>::BatchTooLarge
+        );
+    })
+}
+
+// 批量删除存证
+#[test]
+fn revoke_claims_batch_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claims = vec![vec![0], vec![1]];
+        assert_ok!(PoeSpfModule::create_claims_batch(
+            Origin::signed(1),
+            claims.clone(),
+            None,
+            BatchMode::AllOrNothing
+        ));
+        assert_ok!(PoeSpfModule::revoke_claims_batch(
+            Origin::signed(1),
+            claims.clone(),
+            BatchMode::AllOrNothing
+        ));
+        for claim in claims.iter() {
+            assert_eq!(Proofs::<Test>::get(claim), None);
+            assert!(System::events().iter().any(|r| r.event
+                == PoeEvent::ClaimRevoked(1, claim.clone()).into()));
+        }
+        assert_eq!(Balances::reserved_balance(1), 0);
+    })
+}
+
+// 批量删除时有一个不是自己的，全部回滚
+#[test]
+fn revoke_claims_batch_all_or_nothing_rolls_back() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0], None));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(2), vec![1], None));
+        assert_noop!(
+            PoeSpfModule::revoke_claims_batch(
+                Origin::signed(1),
+                vec![vec![0], vec![1]],
+                BatchMode::AllOrNothing
+            ),
+            Error::<Test>::NotClaimOwner
+        );
+    })
+}
+
+// 批量删除时跳过不是自己的存证
+#[test]
+fn revoke_claims_batch_best_effort_skips_failures() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0], None));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(2), vec![1], None));
+        assert_ok!(PoeSpfModule::revoke_claims_batch(
+            Origin::signed(1),
+            vec![vec![0], vec![1]],
+            BatchMode::BestEffort
+        ));
+        assert_eq!(Proofs::<Test>::get(vec![0]), None);
+        assert_eq!(Proofs::<Test>::get(vec![1]), Some((2, 1)));
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimRevokeFailed(1, vec![1], Error::<Test>::NotClaimOwner.into()).into()
+        );
+    })
+}
+
+fn merkle_leaves(n: u8) -> Vec<merkle::Hash> {
+    (0..n).map(|i| merkle::hash_leaf(&[i])).collect()
+}
+
+// 只有一个叶子时，默克尔根就是叶子本身
+#[test]
+fn merkle_single_leaf_root_is_leaf() {
+    let leaves = merkle_leaves(1);
+    assert_eq!(merkle::merkle_root(&leaves), Some(leaves[0]));
+    assert_eq!(merkle::merkle_proof(&leaves, 0), Some(vec![]));
+    assert!(merkle::verify_proof(&leaves[0], &leaves[0], &[], 1));
+}
+
+// 每个叶子的证明都能通过校验，包括奇数个叶子的情况
+#[test]
+fn merkle_every_leaf_proof_verifies() {
+    for n in 1..=9 {
+        let leaves = merkle_leaves(n);
+        let root = merkle::merkle_root(&leaves).unwrap();
+        for (i, leaf) in leaves.iter().enumerate() {
+            let proof = merkle::merkle_proof(&leaves, i).unwrap();
+            assert_eq!(Some(proof.len()), merkle::proof_len(n as u32));
+            assert!(merkle::verify_proof(&root, leaf, &proof, n as u32));
+        }
+    }
+}
+
+// 不在树里的叶子校验不通过
+#[test]
+fn merkle_wrong_leaf_fails() {
+    let leaves = merkle_leaves(5);
+    let root = merkle::merkle_root(&leaves).unwrap();
+    let proof = merkle::merkle_proof(&leaves, 2).unwrap();
+    assert!(!merkle::verify_proof(&root, &merkle::hash_leaf(b"other"), &proof, 5));
+    assert_eq!(merkle::merkle_root(&[]), None);
+    assert_eq!(merkle::merkle_proof(&leaves, 5), None);
+}
+
+// 两个子节点拼成的64字节文件算不出父节点，缩短的证明校验不通过
+#[test]
+fn merkle_internal_node_is_not_a_leaf() {
+    let leaves = merkle_leaves(4);
+    let root = merkle::merkle_root(&leaves).unwrap();
+    let proof = merkle::merkle_proof(&leaves, 0).unwrap();
+
+    let (first, second) =
+        if leaves[0] <= leaves[1] { (leaves[0], leaves[1]) } else { (leaves[1], leaves[0]) };
+    let forged = merkle::hash_leaf(&[first, second].concat());
+    assert!(!merkle::verify_proof(&root, &forged, &proof[1..], 4));
+    assert!(!merkle::verify_proof(&root, &forged, &proof[1..], 2));
+}
+
+// 证明长度和叶子数对不上的不通过
+#[test]
+fn merkle_proof_length_must_match_leaf_count() {
+    let leaves = merkle_leaves(5);
+    let root = merkle::merkle_root(&leaves).unwrap();
+    let proof = merkle::merkle_proof(&leaves, 4).unwrap();
+    assert_eq!(proof.len(), 3);
+    assert!(merkle::verify_proof(&root, &leaves[4], &proof, 5));
+    assert!(!merkle::verify_proof(&root, &leaves[4], &proof, 4));
+    assert!(!merkle::verify_proof(&root, &leaves[4], &proof, 9));
+    assert!(!merkle::verify_proof(&root, &leaves[4], &proof, 0));
+    let mut longer = proof.clone();
+    longer.push(leaves[0]);
+    assert!(!merkle::verify_proof(&root, &leaves[4], &longer, 5));
+}
+
+// 把默克尔根作为一个存证，不受存证长度限制
+#[test]
+fn create_merkle_claim_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let leaves = merkle_leaves(4);
+        let root = merkle::merkle_root(&leaves).unwrap();
+        assert_ok!(PoeSpfModule::create_merkle_claim(Origin::signed(1), root, 4, None));
+        assert_eq!(Proofs::<Test>::get(root.to_vec()), Some((1, 1)));
+        assert_eq!(MerkleRoots::<Test>::get(root.to_vec()), Some(4));
+        // 押金按32字节算：10 + 32
+        assert_eq!(Balances::reserved_balance(1), 42);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::MerkleRootAnchored(1, root.to_vec(), 4).into()
+        );
+    })
+}
+
+// 默克尔根不能重复存证
+#[test]
+fn create_merkle_claim_failed_when_already_exist() {
+    new_test_ext().execute_with(|| {
+        let root = merkle::merkle_root(&merkle_leaves(2)).unwrap();
+        assert_ok!(PoeSpfModule::create_merkle_claim(Origin::signed(1), root, 2, None));
+        assert_noop!(
+            PoeSpfModule::create_merkle_claim(Origin::signed(2), root, 2, None),
+            Error::<Test>::ProofAlreadyExist
+        );
+    })
+}
+
+// 已存证的默克尔根可以校验包含证明
+#[test]
+fn verify_inclusion_works() {
+    new_test_ext().execute_with(|| {
+        let leaves = merkle_leaves(5);
+        let root = merkle::merkle_root(&leaves).unwrap();
+        let proof = merkle::merkle_proof(&leaves, 3).unwrap();
+
+        // 还没存证的根校验不通过
+        assert!(!PoeSpfModule::verify_inclusion(&root, &leaves[3], &proof));
+
+        assert_ok!(PoeSpfModule::create_merkle_claim(Origin::signed(1), root, 5, None));
+        assert!(PoeSpfModule::verify_inclusion(&root, &leaves[3], &proof));
+        assert!(!PoeSpfModule::verify_inclusion(&root, &leaves[2], &proof));
+        // 去掉一个节点的证明校验不通过
+        assert!(!PoeSpfModule::verify_inclusion(&root, &leaves[3], &proof[1..]));
+
+        // 删除存证以后校验不通过
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), root.to_vec()));
+        assert_eq!(MerkleRoots::<Test>::get(root.to_vec()), None);
+        assert!(!PoeSpfModule::verify_inclusion(&root, &leaves[3], &proof));
+    })
+}
+
+// 发起转移请求，接收者同意后存证和押金才转过去
+#[test]
+fn offer_and_accept_claim_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 2, Some(5)));
+        assert_eq!(PendingOffers::<Test>::get(&claim), Some((2, Some(5))));
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimOffered(1, claim.clone(), 2, Some(5)).into()
+        );
+        // 接收者同意之前所有者不变
+        assert_eq!(Proofs::<Test>::get(&claim), Some((1, 1)));
+
+        run_to_block(2);
+        assert_ok!(PoeSpfModule::accept_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(Proofs::<Test>::get(&claim), Some((2, 2)));
+        assert_eq!(PendingOffers::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 12);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimOfferAccepted(2, claim, 1).into()
+        );
+    })
+}
+
+// 只有所有者能发起转移请求
+#[test]
+fn offer_claim_failed_when_not_owner() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::offer_claim(Origin::signed(2), claim.clone(), 9, None),
+            Error::<Test>::NotClaimOwner
+        );
+        assert_noop!(
+            PoeSpfModule::offer_claim(Origin::signed(1), claim, 1, None),
+            Error::<Test>::OwnerEqualReceiver
+        );
+    })
+}
+
+// 不是接收者不能同意转移
+#[test]
+fn accept_claim_failed_when_not_receiver() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::accept_claim(Origin::signed(2), claim.clone()),
+            Error::<Test>::OfferNotExist
+        );
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 2, None));
+        assert_noop!(
+            PoeSpfModule::accept_claim(Origin::signed(9), claim),
+            Error::<Test>::NotOfferReceiver
+        );
+    })
+}
+
+// 转移请求过期以后不能再同意
+#[test]
+fn accept_claim_failed_when_offer_expired() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 2, Some(3)));
+        run_to_block(3);
+        assert_noop!(
+            PoeSpfModule::accept_claim(Origin::signed(2), claim),
+            Error::<Test>::OfferExpired
+        );
+    })
+}
+
+// 接收者拒绝转移
+#[test]
+fn reject_claim_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 2, None));
+        assert_noop!(
+            PoeSpfModule::reject_claim(Origin::signed(9), claim.clone()),
+            Error::<Test>::NotOfferReceiver
+        );
+        assert_ok!(PoeSpfModule::reject_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(PendingOffers::<Test>::get(&claim), None);
+        assert_eq!(Proofs::<Test>::get(&claim), Some((1, 1)));
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimOfferRejected(2, claim).into()
+        );
+    })
+}
+
+// 所有者取消转移请求
+#[test]
+fn cancel_offer_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::cancel_offer(Origin::signed(1), claim.clone()),
+            Error::<Test>::OfferNotExist
+        );
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 2, None));
+        assert_noop!(
+            PoeSpfModule::cancel_offer(Origin::signed(2), claim.clone()),
+            Error::<Test>::NotClaimOwner
+        );
+        assert_ok!(PoeSpfModule::cancel_offer(Origin::signed(1), claim.clone()));
+        assert_eq!(PendingOffers::<Test>::get(&claim), None);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimOfferCancelled(1, claim.clone()).into()
+        );
+        assert_noop!(
+            PoeSpfModule::accept_claim(Origin::signed(2), claim),
+            Error::<Test>::OfferNotExist
+        );
+    })
+}
+
+// 直接转移或删除存证后，原来的转移请求作废
+#[test]
+fn offer_cleared_on_transfer_and_revoke() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 2, None));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 9));
+        assert_eq!(PendingOffers::<Test>::get(&claim), None);
+
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(9), claim.clone(), 2, None));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(9), claim.clone()));
+        assert_eq!(PendingOffers::<Test>::get(&claim), None);
+    })
+}
+
+// 创建、转移、删除存证时，所有者索引保持一致
+#[test]
+fn owner_claims_index_follows_claims() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0], None));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![1], None));
+        assert!(OwnerClaims::<Test>::contains_key(1, vec![0]));
+        assert!(OwnerClaims::<Test>::contains_key(1, vec![1]));
+
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), vec![0], 2));
+        assert!(!OwnerClaims::<Test>::contains_key(1, vec![0]));
+        assert!(OwnerClaims::<Test>::contains_key(2, vec![0]));
+
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), vec![1]));
+        assert!(!OwnerClaims::<Test>::contains_key(1, vec![1]));
+        assert!(PoeSpfModule::claims_of(&1, 0, 10).is_empty());
+        assert_eq!(PoeSpfModule::claims_of(&2, 0, 10), vec![(vec![0], 0)]);
+    })
+}
+
+// 过期的存证也从所有者索引里删除
+#[test]
+fn owner_claims_index_cleared_on_expiry() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0], Some(2)));
+        run_to_block(2);
+        assert!(!OwnerClaims::<Test>::contains_key(1, vec![0]));
+    })
+}
+
+// 分页列出账户的存证
+#[test]
+fn claims_of_is_paginated() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(PoeSpfModule::create_claims_batch(
+            Origin::signed(1),
+            vec![vec![0], vec![1], vec![2]],
+            None,
+            BatchMode::AllOrNothing
+        ));
+        let all = PoeSpfModule::claims_of(&1, 0, 10);
+        assert_eq!(all.len(), 3);
+
+        // 分页拼起来和一次查出来的一样
+        let mut pages = PoeSpfModule::claims_of(&1, 0, 2);
+        assert_eq!(pages.len(), 2);
+        pages.extend(PoeSpfModule::claims_of(&1, 2, 2));
+        assert_eq!(pages, all);
+
+        assert!(PoeSpfModule::claims_of(&1, 3, 2).is_empty());
+    })
+}
+
+// 每次转移都记录原所有者、新所有者和区块
+#[test]
+fn transfer_claim_records_history() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert!(ClaimHistory::<Test>::get(&claim).is_empty());
+
+        run_to_block(2);
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+        run_to_block(3);
+        assert_ok!(PoeSpfModule::offer_claim(Origin::signed(2), claim.clone(), 9, None));
+        assert_ok!(PoeSpfModule::accept_claim(Origin::signed(9), claim.clone()));
+
+        assert_eq!(ClaimHistory::<Test>::get(&claim).into_inner(), vec![(1, 2, 2), (2, 9, 3)]);
+    })
+}
+
+// 记录超过上限时丢掉最早的记录
+#[test]
+fn claim_history_is_bounded() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(2), claim.clone(), 9));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(9), claim.clone(), 1));
+
+        assert_eq!(ClaimHistory::<Test>::get(&claim).into_inner(), vec![(2, 9, 0), (9, 1, 0)]);
+    })
+}
+
+// 删除存证时记录也删除
+#[test]
+fn claim_history_cleared_on_revoke() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 2));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(2), claim.clone()));
+        assert!(ClaimHistory::<Test>::get(&claim).is_empty());
+    })
+}
+
+// ForceOrigin强制删除存证，押金返还给所有者
+#[test]
+fn force_revoke_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::force_revoke(Origin::signed(2), claim.clone(), 7),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(PoeSpfModule::force_revoke(Origin::root(), claim.clone(), 7));
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+        assert!(!OwnerClaims::<Test>::contains_key(1, &claim));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimForceRevoked(1, claim, 7).into()
+        );
+    })
+}
+
+// ForceOrigin强制转移存证，押金直接转成接收者锁定的押金
+#[test]
+fn force_transfer_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::force_transfer(Origin::signed(1), claim.clone(), 3, 7),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        // 用户3的钱不够交押金，强制转移也能成功
+        assert_ok!(PoeSpfModule::force_transfer(Origin::root(), claim.clone(), 3, 7));
+        assert_eq!(Proofs::<Test>::get(&claim), Some((3, 1)));
+        assert!(OwnerClaims::<Test>::contains_key(3, &claim));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 88);
+        assert_eq!(Balances::reserved_balance(3), 12);
+        assert_eq!(ClaimHistory::<Test>::get(&claim).into_inner(), vec![(1, 3, 1)]);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimForceTransferred(1, claim, 3, 7).into()
+        );
+    })
+}
+
+// 有争议的存证被冻结，所有者不能修改
+#[test]
+fn open_dispute_freezes_claim() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::open_dispute(Origin::signed(2), claim.clone(), 3));
+        assert_eq!(Disputes::<Test>::get(&claim), Some((2, 3, 1)));
+        assert_eq!(Balances::reserved_balance(2), 5);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::DisputeOpened(2, claim.clone(), 3).into()
+        );
+
+        assert_noop!(
+            PoeSpfModule::open_dispute(Origin::signed(9), claim.clone(), 3),
+            Error::<Test>::DisputeAlreadyOpen
+        );
+        assert_noop!(
+            PoeSpfModule::revoke_claim(Origin::signed(1), claim.clone()),
+            Error::<Test>::ClaimFrozen
+        );
+        assert_noop!(
+            PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 9),
+            Error::<Test>::ClaimFrozen
+        );
+        assert_noop!(
+            PoeSpfModule::offer_claim(Origin::signed(1), claim.clone(), 9, None),
+            Error::<Test>::ClaimFrozen
+        );
+        assert_noop!(
+            PoeSpfModule::renew_claim(Origin::signed(1), claim, Some(10)),
+            Error::<Test>::ClaimFrozen
+        );
+    })
+}
+
+// 有争议的存证到期时不删除，争议处理完以后再过期
+#[test]
+fn disputed_claim_expires_after_resolution() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), Some(3)));
+        assert_ok!(PoeSpfModule::open_dispute(Origin::signed(2), claim.clone(), 3));
+
+        // 过了过期区块，存证和争议都还在，过期挪到下一个区块
+        run_to_block(5);
+        assert!(Proofs::<Test>::contains_key(&claim));
+        assert_eq!(Disputes::<Test>::get(&claim), Some((2, 3, 1)));
+        assert_eq!(ClaimExpiry::<Test>::get(&claim), Some(6));
+        assert_eq!(ExpiryQueue::<Test>::get(6).into_inner(), vec![claim.clone()]);
+        assert_eq!(Balances::reserved_balance(2), 5);
+
+        // 争议不成立，罚没争议押金，存证在下一个区块过期
+        assert_ok!(PoeSpfModule::resolve_dispute(Origin::root(), claim.clone(), false));
+        run_to_block(6);
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(2), 95);
+    })
+}
+
+// 下一个区块的清理队列满了时，有争议的存证挪到后面还有空位的区块
+#[test]
+fn disputed_claim_expiry_skips_full_blocks() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0], Some(3)));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![1], Some(4)));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![2], Some(4)));
+        assert_ok!(PoeSpfModule::open_dispute(Origin::signed(2), vec![0], 3));
+
+        run_to_block(3);
+        assert_eq!(ClaimExpiry::<Test>::get(vec![0]), Some(5));
+        assert_eq!(ExpiryQueue::<Test>::get(5).into_inner(), vec![vec![0]]);
+    })
+}
+
+// 不能对不存在的存证发起争议
+#[test]
+fn open_dispute_failed_when_claim_not_exist() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeSpfModule::open_dispute(Origin::signed(2), vec![0, 1], 3),
+            Error::<Test>::ClaimNotExist
+        );
+    })
+}
+
+// 争议成立：删除存证，返还两边的押金
+#[test]
+fn resolve_dispute_upheld_revokes_claim() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::open_dispute(Origin::signed(2), claim.clone(), 3));
+        assert_noop!(
+            PoeSpfModule::resolve_dispute(Origin::signed(2), claim.clone(), true),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_ok!(PoeSpfModule::resolve_dispute(Origin::root(), claim.clone(), true));
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+        assert_eq!(Disputes::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 100);
+        assert!(System::events().iter().any(|r| r.event
+            == PoeEvent::ClaimForceRevoked(1, claim.clone(), 3).into()));
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::DisputeResolved(claim, true).into()
+        );
+    })
+}
+
+// 争议不成立：罚没争议押金，存证解冻
+#[test]
+fn resolve_dispute_dismissed_unfreezes_claim() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::open_dispute(Origin::signed(2), claim.clone(), 3));
+        assert_ok!(PoeSpfModule::resolve_dispute(Origin::root(), claim.clone(), false));
+        assert_eq!(Disputes::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(2), 95);
+        assert_eq!(Proofs::<Test>::get(&claim), Some((1, 0)));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim, 9));
+    })
+}
+
+// 处理不存在的争议
+#[test]
+fn resolve_dispute_failed_when_not_exist() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            PoeSpfModule::resolve_dispute(Origin::root(), vec![0, 1], true),
+            Error::<Test>::DisputeNotExist
+        );
+    })
+}
+
+// 创建共有存证，创建者自己也是共有人
+#[test]
+fn create_shared_claim_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2, 9],
+            2,
+            None
+        ));
+        let (owners, threshold) = CoOwners::<Test>::get(&claim).expect("co-owners expected");
+        assert_eq!(owners.into_inner(), vec![1, 2, 9]);
+        assert_eq!(threshold, 2);
+        assert_eq!(Proofs::<Test>::get(&claim), Some((1, 1)));
+        assert_eq!(Balances::reserved_balance(1), 12);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::SharedClaimCreated(1, claim, vec![1, 2, 9], 2).into()
+        );
+    })
+}
+
+// 共有人数和同意数不合法时不能创建共有存证
+#[test]
+fn create_shared_claim_failed_when_owners_or_threshold_invalid() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_noop!(
+            PoeSpfModule::create_shared_claim(Origin::signed(1), claim.clone(), vec![2, 9], 0, None),
+            Error::<Test>::InvalidThreshold
+        );
+        assert_noop!(
+            PoeSpfModule::create_shared_claim(Origin::signed(1), claim.clone(), vec![2, 9], 4, None),
+            Error::<Test>::InvalidThreshold
+        );
+        assert_noop!(
+            PoeSpfModule::create_shared_claim(Origin::signed(1), claim.clone(), vec![2, 2], 2, None),
+            Error::<Test>::DuplicateCoOwner
+        );
+        assert_noop!(
+            PoeSpfModule::create_shared_claim(Origin::signed(1), claim, vec![2, 3, 9], 2, None),
+            Error::<Test>::TooManyCoOwners
+        );
+    })
+}
+
+// 同意数不够时只记录同意，达到门槛时才删除存证
+#[test]
+fn revoke_shared_claim_after_threshold_reached() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2, 9],
+            2,
+            None
+        ));
+
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(2), claim.clone()));
+        assert!(Proofs::<Test>::contains_key(&claim));
+        assert_eq!(
+            PendingApprovals::<Test>::get(&claim, ClaimAction::Revoke).into_inner(),
+            vec![2]
+        );
+        // 第一个同意的共有人锁定待执行操作的押金
+        assert_eq!(ApprovalDeposits::<Test>::get(&claim, ClaimAction::Revoke), Some((2, 2)));
+        assert_eq!(Balances::reserved_balance(2), 2);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ApprovalRecorded(2, claim.clone(), 1).into()
+        );
+
+        assert_noop!(
+            PoeSpfModule::revoke_claim(Origin::signed(2), claim.clone()),
+            Error::<Test>::AlreadyApproved
+        );
+        assert_noop!(
+            PoeSpfModule::revoke_claim(Origin::signed(3), claim.clone()),
+            Error::<Test>::NotClaimOwner
+        );
+
+        // 第二个共有人同意，存证被删除，押金返还给创建者，待执行操作的押金也返还
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(9), claim.clone()));
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+        assert_eq!(CoOwners::<Test>::get(&claim), None);
+        assert_eq!(PendingApprovals::<Test>::iter_prefix(&claim).count(), 0);
+        assert_eq!(ApprovalDeposits::<Test>::iter_prefix(&claim).count(), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::reserved_balance(9), 0);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimRevoked(1, claim).into()
+        );
+    })
+}
+
+// 同意数为1时任何一个共有人都能直接删除
+#[test]
+fn revoke_shared_claim_with_threshold_one() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2],
+            1,
+            None
+        ));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    })
+}
+
+// 同意数等于共有人数时需要所有共有人同意
+#[test]
+fn revoke_shared_claim_with_threshold_all() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2, 9],
+            3,
+            None
+        ));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(9), claim.clone()));
+        assert!(Proofs::<Test>::contains_key(&claim));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(2), claim.clone()));
+        assert_eq!(Proofs::<Test>::get(&claim), None);
+    })
+}
+
+// 转给不同接收者的同意分开计数，转移以后存证不再共有
+#[test]
+fn transfer_shared_claim_counts_approvals_per_receiver() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2, 3],
+            2,
+            None
+        ));
+
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 9));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(3), claim.clone(), 2));
+        assert_eq!(Proofs::<Test>::get(&claim), Some((1, 1)));
+        // 每个待执行操作各有一份押金：存证押金12 + 操作押金2
+        assert_eq!(Balances::reserved_balance(1), 14);
+        assert_eq!(Balances::reserved_balance(3), 2);
+
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(2), claim.clone(), 9));
+        assert_eq!(Proofs::<Test>::get(&claim), Some((9, 1)));
+        assert_eq!(CoOwners::<Test>::get(&claim), None);
+        assert_eq!(PendingApprovals::<Test>::iter_prefix(&claim).count(), 0);
+        assert_eq!(ApprovalDeposits::<Test>::iter_prefix(&claim).count(), 0);
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(3), 0);
+        assert_eq!(Balances::reserved_balance(9), 12);
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimTransfer(1, claim.clone(), 9).into()
+        );
+
+        // 现在是普通存证，原来的共有人不能再操作
+        assert_noop!(
+            PoeSpfModule::revoke_claim(Origin::signed(2), claim),
+            Error::<Test>::NotClaimOwner
+        );
+    })
+}
+
+// 接收者的钱不够时转移失败，这次同意也不会被记录
+#[test]
+fn transfer_shared_claim_failed_when_receiver_cannot_pay() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2],
+            1,
+            None
+        ));
+        assert_noop!(
+            PoeSpfModule::transfer_claim(Origin::signed(2), claim.clone(), 3),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+        assert_eq!(
+            PendingApprovals::<Test>::get(&claim, ClaimAction::Transfer(3)).len(),
+            0
+        );
+        assert_eq!(ApprovalDeposits::<Test>::get(&claim, ClaimAction::Transfer(3)), None);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    })
+}
+
+// 锁定不了待执行操作的押金时不能同意
+#[test]
+fn approve_shared_claim_failed_when_cannot_cover_deposit() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![3],
+            2,
+            None
+        ));
+        assert_ok!(Balances::reserve(&3, 4));
+        assert_noop!(
+            PoeSpfModule::revoke_claim(Origin::signed(3), claim),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+    })
+}
+
+// 共有存证不能通过转移请求转移
+#[test]
+fn offer_shared_claim_failed() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2],
+            2,
+            None
+        ));
+        assert_noop!(
+            PoeSpfModule::offer_claim(Origin::signed(1), claim, 9, None),
+            Error::<Test>::ClaimIsShared
+        );
+    })
+}
+
+// 共有存证的过期区块不能由创建者一个人修改，否则可以绕过共有人直接让存证过期
+#[test]
+fn renew_shared_claim_failed() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_shared_claim(
+            Origin::signed(1),
+            claim.clone(),
+            vec![2],
+            2,
+            None
+        ));
+        assert_noop!(
+            PoeSpfModule::renew_claim(Origin::signed(1), claim.clone(), Some(2)),
+            Error::<Test>::ClaimIsShared
+        );
+
+        run_to_block(2);
+        assert!(Proofs::<Test>::contains_key(&claim));
+    })
+}
+
+// 按v2的布局写一个存证
+fn put_v2_proof(claim: &[u8], owner: u64, block_number: u64) {
+    let key = Blake2_128Concat::hash(&claim.to_vec().encode());
+    put_storage_value(b"TemplateModule", b"Proofs", &key, (owner, block_number));
+}
+
+fn v2_proof(claim: &[u8]) -> Option<(u64, u64)> {
+    let key = Blake2_128Concat::hash(&claim.to_vec().encode());
+    get_storage_value(b"TemplateModule", b"Proofs", &key)
+}
+
+// 新链创世时就是最新的存储版本
+#[test]
+fn genesis_sets_latest_storage_version() {
+    new_test_ext().execute_with(|| {
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+
+        // 不会再去搬v2的存证
+        put_v2_proof(&[0, 1], 1, 3);
+        PoeSpfModule::on_runtime_upgrade();
+        assert_eq!(Proofs::<Test>::get(vec![0, 1]), None);
+    })
+}
+
+// v2的存证搬到v3，并且只迁移一次
+#[test]
+fn migrate_v2_proofs_works() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<Test>::put(Releases::V2);
+        put_v2_proof(&[0, 1], 1, 3);
+        put_v2_proof(&[2], 2, 4);
+
+        assert_ok!(pre_migrate_to_v3::<Test>());
+        PoeSpfModule::on_runtime_upgrade();
+        assert_ok!(post_migrate_to_v3::<Test>());
+
+        assert_eq!(Proofs::<Test>::get(vec![0, 1]), Some((1, 3)));
+        assert_eq!(Proofs::<Test>::get(vec![2]), Some((2, 4)));
+        assert!(OwnerClaims::<Test>::contains_key(1, vec![0, 1]));
+        assert!(OwnerClaims::<Test>::contains_key(2, vec![2]));
+        assert_eq!(v2_proof(&[0, 1]), None);
+        assert_eq!(v2_proof(&[2]), None);
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+
+        // 迁移时和新建存证一样锁定押金，删除时返还
+        assert_eq!(ClaimDeposits::<Test>::get(vec![0, 1]), 12);
+        assert_eq!(Balances::reserved_balance(1), 12);
+        assert_eq!(ClaimDeposits::<Test>::get(vec![2]), 11);
+        assert_eq!(Balances::reserved_balance(2), 11);
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), vec![0, 1]));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::free_balance(1), 100);
+
+        // 已经是V3时不会再迁移
+        put_v2_proof(&[3], 1, 5);
+        PoeSpfModule::on_runtime_upgrade();
+        assert_eq!(Proofs::<Test>::get(vec![3]), None);
+        assert_eq!(v2_proof(&[3]), Some((1, 5)));
+    })
+}
+
+// 所有者的钱不够时存证照样迁移，不收押金
+#[test]
+fn migrate_v2_proofs_without_deposit() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<Test>::put(Releases::V2);
+        put_v2_proof(&[0, 1], 3, 3);
+
+        assert_ok!(pre_migrate_to_v3::<Test>());
+        PoeSpfModule::on_runtime_upgrade();
+        assert_ok!(post_migrate_to_v3::<Test>());
+
+        assert_eq!(Proofs::<Test>::get(vec![0, 1]), Some((3, 3)));
+        assert_eq!(ClaimDeposits::<Test>::get(vec![0, 1]), 0);
+        assert_eq!(Balances::reserved_balance(3), 0);
+
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(3), vec![0, 1]));
+        assert_eq!(Balances::free_balance(3), 5);
+    })
+}
+
+// 一个区块搬不完的存证在之后的区块里接着搬
+#[test]
+fn migrate_v2_proofs_across_blocks() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<Test>::put(Releases::V2);
+        let claims: Vec<Vec<u8>> =
+            (0..=MIGRATION_BATCH as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        for claim in &claims {
+            put_v2_proof(claim, 3, 1);
+        }
+
+        assert_ok!(pre_migrate_to_v3::<Test>());
+        PoeSpfModule::on_runtime_upgrade();
+        assert_ok!(post_migrate_to_v3::<Test>());
+        assert_eq!(Proofs::<Test>::iter().count(), MIGRATION_BATCH);
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V2);
+
+        run_to_block(1);
+        assert_ok!(post_migrate_to_v3::<Test>());
+        assert_eq!(StorageVersion::<Test>::get(), Releases::V3);
+        for claim in &claims {
+            assert_eq!(Proofs::<Test>::get(claim), Some((3, 1)));
+            assert_eq!(v2_proof(claim), None);
+        }
+    })
+}
+
+// v3里已经有的存证保留v3的所有者
+#[test]
+fn migrate_v2_proofs_keeps_existing_claims() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<Test>::put(Releases::V2);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(9), vec![2], None));
+        put_v2_proof(&[2], 2, 4);
+
+        assert_ok!(pre_migrate_to_v3::<Test>());
+        PoeSpfModule::on_runtime_upgrade();
+        assert_ok!(post_migrate_to_v3::<Test>());
+
+        assert_eq!(Proofs::<Test>::get(vec![2]), Some((9, 0)));
+        assert!(!OwnerClaims::<Test>::contains_key(2, vec![2]));
+        assert_eq!(v2_proof(&[2]), None);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    })
+}
+
+// 迁移期间还没搬过来的v2存证不能被别人抢先创建
+#[test]
+fn create_pending_v2_claim_failed() {
+    new_test_ext().execute_with(|| {
+        StorageVersion::<Test>::put(Releases::V2);
+        let claims: Vec<Vec<u8>> =
+            (0..=MIGRATION_BATCH as u32).map(|i| i.to_le_bytes().to_vec()).collect();
+        for claim in &claims {
+            put_v2_proof(claim, 2, 1);
+        }
+        PoeSpfModule::on_runtime_upgrade();
+
+        // 剩下的那个存证还在v2里
+        let pending = claims
+            .iter()
+            .find(|claim| v2_proof(claim).is_some())
+            .expect("one claim left in v2")
+            .clone();
+        assert_noop!(
+            PoeSpfModule::create_claim(Origin::signed(9), pending.clone(), None),
+            Error::<Test>::ProofAlreadyExist
+        );
+
+        // 搬完之后归原来的所有者
+        run_to_block(1);
+        assert_eq!(Proofs::<Test>::get(&pending), Some((2, 1)));
+        assert_ok!(post_migrate_to_v3::<Test>());
+    })
+}
+
+// 设置存证的描述信息
+#[test]
+fn set_claim_metadata_works() {
+    new_test_ext().execute_with(|| {
+        run_to_block(1);
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::set_claim_metadata(
+            Origin::signed(1),
+            claim.clone(),
+            b"deed".to_vec()
+        ));
+        assert_eq!(ClaimMetadata::<Test>::get(&claim).into_inner(), b"deed".to_vec());
+        assert_eq!(System::events().last().expect("events expected").event,
+            PoeEvent::ClaimMetadataSet(1, claim.clone()).into()
+        );
+        // 存证押金12 + 描述信息押金10 + 4
+        assert_eq!(MetadataDeposits::<Test>::get(&claim), Some((1, 14)));
+        assert_eq!(Balances::reserved_balance(1), 26);
+
+        // 覆盖时按新的长度锁定，原来的押金返还
+        assert_ok!(PoeSpfModule::set_claim_metadata(Origin::signed(1), claim.clone(), b"d".to_vec()));
+        assert_eq!(MetadataDeposits::<Test>::get(&claim), Some((1, 11)));
+        assert_eq!(Balances::reserved_balance(1), 23);
+
+        // 删除存证时描述信息也删掉，押金返还
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(1), claim.clone()));
+        assert!(!ClaimMetadata::<Test>::contains_key(&claim));
+        assert_eq!(MetadataDeposits::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 0);
+    })
+}
+
+// 空的描述信息表示删除，返还押金；转移以后押金还是返还给设置的人
+#[test]
+fn set_claim_metadata_refunds_depositor() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_ok!(PoeSpfModule::set_claim_metadata(Origin::signed(1), claim.clone(), b"deed".to_vec()));
+        assert_ok!(PoeSpfModule::set_claim_metadata(Origin::signed(1), claim.clone(), vec![]));
+        assert!(!ClaimMetadata::<Test>::contains_key(&claim));
+        assert_eq!(MetadataDeposits::<Test>::get(&claim), None);
+        assert_eq!(Balances::reserved_balance(1), 12);
+
+        assert_ok!(PoeSpfModule::set_claim_metadata(Origin::signed(1), claim.clone(), b"deed".to_vec()));
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), claim.clone(), 9));
+        assert_eq!(Balances::reserved_balance(1), 14);
+
+        // 新的所有者覆盖描述信息，原来的所有者拿回押金
+        assert_ok!(PoeSpfModule::set_claim_metadata(Origin::signed(9), claim.clone(), b"new".to_vec()));
+        assert_eq!(MetadataDeposits::<Test>::get(&claim), Some((9, 13)));
+        assert_eq!(Balances::reserved_balance(1), 0);
+        assert_eq!(Balances::reserved_balance(9), 25);
+    })
+}
+
+// 不是所有者、描述信息太长或者存证不存在时不能设置
+#[test]
+fn set_claim_metadata_failed() {
+    new_test_ext().execute_with(|| {
+        let claim = vec![0, 1];
+        assert_noop!(
+            PoeSpfModule::set_claim_metadata(Origin::signed(1), claim.clone(), b"deed".to_vec()),
+            Error::<Test>::ClaimNotExist
+        );
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), claim.clone(), None));
+        assert_noop!(
+            PoeSpfModule::set_claim_metadata(Origin::signed(2), claim.clone(), b"deed".to_vec()),
+            Error::<Test>::NotClaimOwner
+        );
+        assert_noop!(
+            PoeSpfModule::set_claim_metadata(Origin::signed(1), claim.clone(), vec![0; 9]),
+            Error::<Test>::MetadataTooLong
+        );
+
+        // 钱不够锁定押金
+        assert_ok!(Balances::reserve(&1, 87));
+        assert_noop!(
+            PoeSpfModule::set_claim_metadata(Origin::signed(1), claim, b"deed".to_vec()),
+            pallet_balances::Error::<Test>::InsufficientBalance
+        );
+    })
+}
+
+// 带链下工作机和链下数据库的测试环境
+fn offchain_test_ext() -> sp_io::TestExternalities {
+    let (offchain, _state) = TestOffchainExt::new();
+    let mut ext = new_test_ext();
+    ext.register_extension(OffchainDbExt::new(offchain.clone()));
+    ext.register_extension(OffchainWorkerExt::new(offchain));
+    ext
+}
+
+// 像节点导入哈希是hash的区块n之后那样运行链下工作机
+fn run_offchain_worker(n: u64, hash: H256) {
+    frame_system::BlockHash::<Test>::insert(n, hash);
+    PoeSpfModule::offchain_worker(n);
+}
+
+// 查询链下索引，canonical是规范链上的区块哈希
+fn search_index(
+    ext: &mut sp_io::TestExternalities,
+    canonical: &[H256],
+    owner: Option<u64>,
+    from: Option<u64>,
+    to: Option<u64>,
+    metadata_prefix: Option<&[u8]>,
+) -> Vec<ClaimRecord<u64, u64>> {
+    let filter =
+        ClaimFilter { owner, from, to, metadata_prefix: metadata_prefix.map(|p| p.to_vec()) };
+    ext.execute_with(|| {
+        index::search(
+            |key| sp_io::offchain::local_storage_get(StorageKind::PERSISTENT, key),
+            |hash: &H256| canonical.contains(hash),
+            &filter,
+        )
+    })
+}
+
+fn record(claim: &[u8], owner: u64, block_number: u64, metadata: &[u8]) -> ClaimRecord<u64, u64> {
+    ClaimRecord {
+        claim: claim.to_vec(),
+        owner: Some(owner),
+        block_number,
+        metadata: metadata.to_vec(),
+    }
+}
+
+// 创建、设置描述信息、转移和删除存证都会写进链下索引
+#[test]
+fn offchain_index_search_works() {
+    let mut ext = offchain_test_ext();
+    ext.execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0, 1], None));
+        assert_ok!(PoeSpfModule::set_claim_metadata(
+            Origin::signed(1),
+            vec![0, 1],
+            b"deed".to_vec()
+        ));
+        run_offchain_worker(1, block_hash(1));
+
+        run_to_block(2);
+        assert!(IndexedClaims::<Test>::get(1).is_empty());
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![2], None));
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(2), vec![3], None));
+        assert_ok!(PoeSpfModule::set_claim_metadata(
+            Origin::signed(2),
+            vec![3],
+            b"doc".to_vec()
+        ));
+        run_offchain_worker(2, block_hash(2));
+
+        run_to_block(3);
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), vec![2], 9));
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(2), vec![3]));
+        assert_eq!(IndexedClaims::<Test>::get(3), vec![vec![2], vec![3]]);
+        run_offchain_worker(3, block_hash(3));
+    });
+
+    let canonical = [block_hash(1), block_hash(2), block_hash(3)];
+    let deed = record(&[0, 1], 1, 1, b"deed");
+    let transferred = record(&[2], 9, 3, b"");
+
+    assert_eq!(
+        search_index(&mut ext, &canonical, None, None, None, None),
+        vec![deed.clone(), transferred.clone()]
+    );
+    assert_eq!(search_index(&mut ext, &canonical, Some(1), None, None, None), vec![deed.clone()]);
+    assert_eq!(
+        search_index(&mut ext, &canonical, Some(9), None, None, None),
+        vec![transferred.clone()]
+    );
+    assert!(search_index(&mut ext, &canonical, Some(2), None, None, None).is_empty());
+    assert_eq!(
+        search_index(&mut ext, &canonical, None, Some(2), Some(3), None),
+        vec![transferred]
+    );
+    assert_eq!(search_index(&mut ext, &canonical, None, None, Some(1), None), vec![deed.clone()]);
+    assert_eq!(
+        search_index(&mut ext, &canonical, None, None, None, Some(b"de")),
+        vec![deed.clone()]
+    );
+    assert_eq!(
+        search_index(&mut ext, &canonical, Some(1), None, None, Some(b"deed")),
+        vec![deed]
+    );
+    assert!(search_index(&mut ext, &canonical, None, None, None, Some(b"do")).is_empty());
+
+    // 没有在规范链上的区块的记录都查询不到
+    assert!(search_index(&mut ext, &[], None, None, None, None).is_empty());
+}
+
+// 父区块相同的兄弟区块各自的记录都会保留，查询时只用规范链上那个区块的记录
+#[test]
+fn offchain_index_keeps_sibling_blocks_apart() {
+    let mut ext = offchain_test_ext();
+    ext.execute_with(|| {
+        run_to_block(1);
+        assert_ok!(PoeSpfModule::create_claim(Origin::signed(1), vec![0, 1], None));
+        run_offchain_worker(1, block_hash(1));
+
+        // 两个区块2的父区块都是区块1，一个转移了存证，另一个删除了存证
+        run_to_block(2);
+        assert_ok!(PoeSpfModule::transfer_claim(Origin::signed(1), vec![0, 1], 9));
+        run_offchain_worker(2, block_hash(2));
+
+        assert_ok!(PoeSpfModule::revoke_claim(Origin::signed(9), vec![0, 1]));
+        run_offchain_worker(2, H256::repeat_byte(0xff));
+    });
+
+    assert_eq!(
+        search_index(&mut ext, &[block_hash(1), block_hash(2)], None, None, None, None),
+        vec![record(&[0, 1], 9, 2, b"")]
+    );
+    assert!(
+        search_index(&mut ext, &[block_hash(1), H256::repeat_byte(0xff)], None, None, None, None)
+            .is_empty()
+    );
+    assert_eq!(
+        search_index(&mut ext, &[block_hash(1)], Some(1), None, None, None),
+        vec![record(&[0, 1], 1, 1, b"")]
+    );
+}
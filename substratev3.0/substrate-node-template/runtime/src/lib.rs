@@ -64,6 +64,9 @@ pub type Index = u32;
 /// A hash of some data used by the chain.
 pub type Hash = sp_core::H256;
 
+/// Index of a kitty in the kitties pallet.
+pub type KittyIndex = u32;
+
 /// Opaque types. These are used by the CLI to instantiate machinery that don't need to know
 /// the specifics of the runtime. They can then be made to be agnostic over specific formats
 /// of data like extrinsics, allowing for them to continue syncing the network through upgrades
@@ -98,12 +101,12 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
     // The version of the runtime specification. A full node will not attempt to use its native
     //   runtime in substitute for the on-chain Wasm runtime unless all of `spec_name`,
     //   `spec_version`, and `authoring_version` are the same between Wasm and native.
-    // This value is set to 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
-    //   the compatible custom types.
-    spec_version: 100,
+    // This value started at 100 to notify Polkadot-JS App (https://polkadot.js.org/apps) to use
+    //   the compatible custom types, and is bumped with every runtime upgrade.
+    spec_version: 102,
     impl_version: 1,
     apis: RUNTIME_API_VERSIONS,
-    transaction_version: 1,
+    transaction_version: 2,
 };
 
 /// This determines the average expected block time that we are targeting.
@@ -270,6 +273,31 @@ impl pallet_sudo::Config for Runtime {
     type Call = Call;
 }
 
+parameter_types! {
+    pub const AssetDeposit: Balance = 1_000_000;
+    pub const ApprovalDeposit: Balance = 500;
+    pub const StringLimit: u32 = 50;
+    pub const MetadataDepositBase: Balance = 100_000;
+    pub const MetadataDepositPerByte: Balance = 1_000;
+}
+
+/// Fungible assets that kitties can be priced in on the market.
+impl pallet_assets::Config for Runtime {
+    type Event = Event;
+    type Balance = Balance;
+    type AssetId = u32;
+    type Currency = Balances;
+    type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+    type AssetDeposit = AssetDeposit;
+    type MetadataDepositBase = MetadataDepositBase;
+    type MetadataDepositPerByte = MetadataDepositPerByte;
+    type ApprovalDeposit = ApprovalDeposit;
+    type StringLimit = StringLimit;
+    type Freezer = ();
+    type Extra = ();
+    type WeightInfo = pallet_assets::weights::SubstrateWeight<Runtime>;
+}
+
 /// Configure the pallet-template in pallets/template.
 impl pallet_template::Config for Runtime {
     type Event = Event;
@@ -277,20 +305,53 @@ impl pallet_template::Config for Runtime {
 
 parameter_types! {
     pub const ClaimLimit: usize = 4;
+    pub const ClaimDepositBase: Balance = 10_000;
+    pub const ClaimDepositPerByte: Balance = 100;
+    pub const MaxExpiringPerBlock: u32 = 100;
+    pub const MaxBatchSize: u32 = 100;
+    pub const MaxHistoryLen: u32 = 50;
+    pub const DisputeDeposit: Balance = 100_000;
+    pub const MaxCoOwners: u32 = 10;
+    pub const ClaimApprovalDeposit: Balance = 10_000;
+    pub const MaxMetadataLen: u32 = 256;
     pub const MinimumVotingLock: u64 = 100;
+    pub const MaxKittiesOwned: u32 = 100;
+    pub const BreedingCooldown: BlockNumber = 10 * MINUTES;
+    pub const AuctionExtension: BlockNumber = 5 * MINUTES;
+    pub const MaxAuctionsPerBlock: u32 = 20;
+    pub const CreatorRoyalty: Permill = Permill::from_percent(5);
 }
 
 impl pallet_poe_spf::Config for Runtime {
     type Event = Event;
     type ClaimLimit = ClaimLimit;
+    type Currency = Balances;
+    type ClaimDepositBase = ClaimDepositBase;
+    type ClaimDepositPerByte = ClaimDepositPerByte;
+    type MaxExpiringPerBlock = MaxExpiringPerBlock;
+    type MaxBatchSize = MaxBatchSize;
+    type MaxHistoryLen = MaxHistoryLen;
+    type ForceOrigin = frame_system::EnsureRoot<AccountId>;
+    type DisputeDeposit = DisputeDeposit;
+    type MaxCoOwners = MaxCoOwners;
+    type ApprovalDeposit = ClaimApprovalDeposit;
+    type MaxMetadataLen = MaxMetadataLen;
+    type WeightInfo = pallet_poe_spf::weights::SubstrateWeight<Runtime>;
 }
 
 impl pallet_kitties::Config for Runtime {
     type Event = Event;
     type Randomness = RandomnessCollectiveFlip;
     type Currency= Balances;
-    type KittyIndex= u32;
+    type KittyIndex = KittyIndex;
     type MinimumVotingLock = MinimumVotingLock;
+    type MaxKittiesOwned = MaxKittiesOwned;
+    type BreedingCooldown = BreedingCooldown;
+    type AuctionExtension = AuctionExtension;
+    type MaxAuctionsPerBlock = MaxAuctionsPerBlock;
+    type CreatorRoyalty = CreatorRoyalty;
+    type Assets = Assets;
+    type WeightInfo = pallet_kitties::weights::SubstrateWeight<Runtime>;
 }
 
 // Create the runtime by composing the FRAME pallets that were previously configured.
@@ -308,10 +369,11 @@ construct_runtime!(
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
         TransactionPayment: pallet_transaction_payment::{Pallet, Storage},
         Sudo: pallet_sudo::{Pallet, Call, Config<T>, Storage, Event<T>},
+        Assets: pallet_assets::{Pallet, Call, Storage, Event<T>},
         // Include the custom logic from the pallet-template in the runtime.
         TemplateModule: pallet_template::{Pallet, Call, Storage, Event<T>},
-        PoeSpfModule: pallet_poe_spf::{Pallet, Call, Storage, Event<T>},
-        KittiesModule: pallet_kitties::{Pallet, Call, Storage, Event<T>},
+        PoeSpfModule: pallet_poe_spf::{Pallet, Call, Storage, Config, Event<T>},
+        KittiesModule: pallet_kitties::{Pallet, Call, Storage, Config<T>, Event<T>},
     }
 );
 
@@ -469,6 +531,26 @@ impl_runtime_apis! {
         }
     }
 
+    impl pallet_poe_spf_runtime_api::PoeApi<Block, AccountId, BlockNumber> for Runtime {
+        fn verify_inclusion(root: [u8; 32], leaf: [u8; 32], proof: Vec<[u8; 32]>) -> bool {
+            PoeSpfModule::verify_inclusion(&root, &leaf, &proof)
+        }
+
+        fn claims_of(owner: AccountId, start: u32, limit: u32) -> Vec<(Vec<u8>, BlockNumber)> {
+            PoeSpfModule::claims_of(&owner, start, limit)
+        }
+
+        fn claim_history(claim: Vec<u8>) -> Vec<(AccountId, AccountId, BlockNumber)> {
+            PoeSpfModule::claim_history(claim).into_inner()
+        }
+    }
+
+    impl pallet_kitties_runtime_api::KittiesApi<Block, KittyIndex> for Runtime {
+        fn kitty_attributes(kitty_id: KittyIndex) -> Option<pallet_kitties::dna::KittyAttributes> {
+            KittiesModule::kitty_attributes(kitty_id)
+        }
+    }
+
     #[cfg(feature = "runtime-benchmarks")]
     impl frame_benchmarking::Benchmark<Block> for Runtime {
         fn dispatch_benchmark(
@@ -503,6 +585,7 @@ impl_runtime_apis! {
 
             add_benchmark!(params, batches, frame_system, SystemBench::<Runtime>);
             add_benchmark!(params, batches, pallet_balances, Balances);
+            add_benchmark!(params, batches, pallet_assets, Assets);
             add_benchmark!(params, batches, pallet_timestamp, Timestamp);
             add_benchmark!(params, batches, pallet_template, TemplateModule);
             add_benchmark!(params, batches, pallet_poe_spf, PoeSpfModule);